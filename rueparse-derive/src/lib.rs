@@ -0,0 +1,324 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    Data, DeriveInput, Error, Expr, Fields, GenericArgument, Ident, LitStr, PathArguments, Type,
+    parse_macro_input,
+};
+
+/// Generates `from_reader` from the field order, reading every field through `UeRead`.
+///
+/// With `#[ue(archive)]` on the struct, `from_archive` taking an `FAssetArchive` is generated
+/// instead, which enables the engine version gates.
+///
+/// Field attributes, all inside `#[ue(...)]`:
+/// - `if = "expr"`: only read the field when `expr` holds. Earlier fields and `reader` are in scope.
+/// - `ue4 = "VERSION"`, `ue5 = "VERSION"`: only read at or after that object version.
+/// - `before_ue4 = "VERSION"`, `before_ue5 = "VERSION"`: only read before that object version.
+/// - `default = "expr"`: value of a field that is not read, `Default::default()` otherwise.
+///   `Option<T>` fields are read as `T` and become `None` when not read.
+/// - `len = "expr"`: read a `Vec<T>` with a count from an earlier field instead of an `i32` prefix.
+///   `[T; N]` arrays are read as `N` elements and need no attribute.
+/// - `pad = N`: skip `N` bytes before the field.
+/// - `with = "path"`: read the field, or each element with `len`, with `path(reader)`.
+/// - `check = "expr"`, `error = "message"`: fail with `InvalidData` when `expr` does not hold
+///   right after the field is read.
+/// - `skip`: never read the field.
+///
+/// Errors are `rueparse::errors::Error`s with the failing field path and offset. The outermost
+/// `from_reader` or `from_archive` call adds the struct name.
+#[proc_macro_derive(UeRead, attributes(ue))]
+pub fn derive_ue_read(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+#[derive(Default)]
+struct FieldOptions {
+    conditions: Vec<Expr>,
+    default: Option<Expr>,
+    len: Option<Expr>,
+    pad: Option<Expr>,
+    with: Option<Expr>,
+    check: Option<Expr>,
+    error: Option<LitStr>,
+    skip: bool,
+}
+
+fn version_condition(function: &str, version_enum: &str, value: &LitStr, negate: bool) -> Expr {
+    let function = format_ident!("{}", function);
+    let version_enum = format_ident!("{}", version_enum);
+    let version = format_ident!("{}", value.value(), span = value.span());
+    let condition = quote! {
+        reader.#function(::rueparse::versions::#version_enum::#version)
+    };
+    if negate {
+        syn::parse_quote!(!#condition)
+    } else {
+        syn::parse_quote!(#condition)
+    }
+}
+
+fn parse_struct_options(input: &DeriveInput) -> syn::Result<bool> {
+    let mut archive = false;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("ue")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("archive") {
+                archive = true;
+                Ok(())
+            } else {
+                Err(meta.error("unknown struct attribute, expected `archive`"))
+            }
+        })?;
+    }
+    Ok(archive)
+}
+
+fn parse_field_options(attrs: &[syn::Attribute], archive: bool) -> syn::Result<FieldOptions> {
+    let mut options = FieldOptions::default();
+    for attr in attrs.iter().filter(|a| a.path().is_ident("ue")) {
+        attr.parse_nested_meta(|meta| {
+            let key = match meta.path.get_ident() {
+                Some(i) => i.to_string(),
+                None => return Err(meta.error("expected an attribute name")),
+            };
+            match key.as_str() {
+                "skip" => options.skip = true,
+                "pad" => options.pad = Some(meta.value()?.parse()?),
+                "error" => options.error = Some(meta.value()?.parse()?),
+                "if" | "default" | "len" | "with" | "check" => {
+                    let value: LitStr = meta.value()?.parse()?;
+                    let expr: Expr = value.parse()?;
+                    match key.as_str() {
+                        "if" => options.conditions.push(expr),
+                        "default" => options.default = Some(expr),
+                        "len" => options.len = Some(expr),
+                        "with" => options.with = Some(expr),
+                        _ => options.check = Some(expr),
+                    }
+                }
+                "ue4" | "ue5" | "before_ue4" | "before_ue5" => {
+                    if !archive {
+                        return Err(meta.error("version gates need `#[ue(archive)]` on the struct"));
+                    }
+                    let value: LitStr = meta.value()?.parse()?;
+                    let (function, version_enum) = if key.ends_with("ue4") {
+                        ("ue4_at_least", "EUnrealEngineObjectUE4Version")
+                    } else {
+                        ("ue5_at_least", "EUnrealEngineObjectUE5Version")
+                    };
+                    options.conditions.push(version_condition(
+                        function,
+                        version_enum,
+                        &value,
+                        key.starts_with("before"),
+                    ));
+                }
+                _ => return Err(meta.error(format!("unknown field attribute `{}`", key))),
+            }
+            Ok(())
+        })?;
+    }
+    if options.check.is_some() != options.error.is_some() {
+        return Err(Error::new_spanned(
+            &attrs[0],
+            "`check` and `error` must be used together",
+        ));
+    }
+    Ok(options)
+}
+
+/// Returns `T` for `Option<T>` and `Vec<T>` when the last path segment is `wrapper`.
+fn inner_type<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    let path = match ty {
+        Type::Path(p) if p.qself.is_none() => &p.path,
+        _ => return None,
+    };
+    let segment = path.segments.last()?;
+    if segment.ident != wrapper {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 1 => match &args.args[0] {
+            GenericArgument::Type(t) => Some(t),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// An expression giving `io::Result<ty>`.
+fn read_value(ty: &Type, with: &Option<Expr>) -> TokenStream2 {
+    match with {
+        Some(with) => quote! { #with(reader) },
+        None => quote! { <#ty as ::rueparse::readers::UeRead>::ue_read(&mut *reader) },
+    }
+}
+
+fn read_field(
+    name: &Ident,
+    field: &str,
+    ty: &Type,
+    options: &FieldOptions,
+) -> syn::Result<TokenStream2> {
+    if options.skip {
+        return Ok(match &options.default {
+            Some(default) => quote! { let #name: #ty = #default; },
+            None => quote! { let #name: #ty = ::std::default::Default::default(); },
+        });
+    }
+
+    let option_inner = if options.conditions.is_empty() {
+        None
+    } else {
+        inner_type(ty, "Option")
+    };
+    let value_type = option_inner.unwrap_or(ty);
+
+    let mut read = match &options.len {
+        Some(len) => {
+            let element = match inner_type(value_type, "Vec") {
+                Some(e) => e,
+                None => return Err(Error::new_spanned(ty, "`len` needs a `Vec<T>` field")),
+            };
+            let read_element = read_value(element, &options.with);
+            quote! {
+                (|| -> ::std::io::Result<::std::vec::Vec<#element>> {
+                    let length = (#len) as u64;
+                    ::rueparse::readers::Reader::check_allocation(&mut *reader, length, 1)?;
+                    let mut values = ::std::vec::Vec::with_capacity(length as usize);
+                    for i in 0..length {
+                        match #read_element {
+                            Ok(v) => values.push(v),
+                            Err(e) => return Err(::rueparse::errors::at_index(e, i as usize)),
+                        }
+                    }
+                    Ok(values)
+                })()
+            }
+        }
+        None => read_value(value_type, &options.with),
+    };
+    if let Some(pad) = &options.pad {
+        read = quote! {
+            match ::rueparse::readers::Reader::skip(&mut *reader, (#pad) as u64) {
+                Ok(_) => #read,
+                Err(e) => Err(e),
+            }
+        };
+    }
+
+    let value = if options.conditions.is_empty() {
+        read
+    } else {
+        let conditions = &options.conditions;
+        let (read, default) = match (option_inner, &options.default) {
+            (Some(_), None) => (quote! { (#read).map(Some) }, quote! { None }),
+            (_, Some(default)) => (read, quote! { #default }),
+            (None, None) => (read, quote! { ::std::default::Default::default() }),
+        };
+        quote! {
+            if #((#conditions))&&* {
+                #read
+            } else {
+                Ok(#default)
+            }
+        }
+    };
+
+    // Every error of the read is given the field as context
+    let mut tokens = quote! {
+        let __ue_result: ::std::io::Result<#ty> = #value;
+        let #name: #ty = match __ue_result {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(::rueparse::errors::field_error(e, &mut *reader, #field));
+            }
+        };
+    };
+
+    if let (Some(check), Some(error)) = (&options.check, &options.error) {
+        tokens = quote! {
+            let __ue_offset = ::rueparse::readers::Reader::position(&mut *reader)?;
+            #tokens
+            if !(#check) {
+                let error = ::rueparse::errors::Error::new(
+                    ::std::io::ErrorKind::InvalidData,
+                    #error,
+                );
+                return Err(error.at(__ue_offset).within(#field).into());
+            }
+        };
+    }
+    Ok(tokens)
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let archive = parse_struct_options(&input)?;
+    let data = match &input.data {
+        Data::Struct(s) => s,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "UeRead can only be derived for structs",
+            ));
+        }
+    };
+
+    let mut reads = Vec::new();
+    let mut names = Vec::new();
+    for (i, field) in data.fields.iter().enumerate() {
+        let (name, display) = match &field.ident {
+            Some(ident) => (ident.clone(), ident.to_string()),
+            None => (format_ident!("field_{}", i), i.to_string()),
+        };
+        let options = parse_field_options(&field.attrs, archive)?;
+        reads.push(read_field(&name, &display, &field.ty, &options)?);
+        names.push(name);
+    }
+    let construct = match &data.fields {
+        Fields::Named(_) => quote! { Self { #(#names),* } },
+        Fields::Unnamed(_) => quote! { Self(#(#names),*) },
+        Fields::Unit => quote! { Self },
+    };
+
+    let ident = &input.ident;
+    let struct_name = ident.to_string();
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    if archive {
+        return Ok(quote! {
+            impl #impl_generics #ident #ty_generics #where_clause {
+                pub fn from_archive(
+                    reader: &mut ::rueparse::assets::reader::FAssetArchive<'_>,
+                ) -> ::std::io::Result<Self> {
+                    let result = (|| -> ::std::io::Result<Self> {
+                        #(#reads)*
+                        Ok(#construct)
+                    })();
+                    result.map_err(|e| ::rueparse::errors::within(e, #struct_name))
+                }
+            }
+        });
+    }
+    // Nested reads go through `ue_read`, so only the outermost structure adds its name
+    Ok(quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            pub fn from_reader(
+                reader: &mut dyn ::rueparse::readers::Reader,
+            ) -> ::std::io::Result<Self> {
+                <Self as ::rueparse::readers::UeRead>::ue_read(reader)
+                    .map_err(|e| ::rueparse::errors::within(e, #struct_name))
+            }
+        }
+
+        impl #impl_generics ::rueparse::readers::UeRead for #ident #ty_generics #where_clause {
+            fn ue_read(reader: &mut dyn ::rueparse::readers::Reader) -> ::std::io::Result<Self> {
+                #(#reads)*
+                Ok(#construct)
+            }
+        }
+    })
+}
@@ -1 +1,3 @@
 pub mod exports;
+pub mod objects;
+pub mod reader;
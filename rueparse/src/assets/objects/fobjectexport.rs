@@ -0,0 +1,62 @@
+use std::io;
+
+use super::FPackageIndex;
+use crate::assets::reader::FAssetArchive;
+use crate::models::FGuid;
+use crate::objects::FName;
+use crate::readers::{Reader, UeRead};
+use crate::versions::EUnrealEngineObjectUE4Version;
+
+#[derive(Debug, Clone, UeRead)]
+#[ue(archive)]
+pub struct FObjectExport {
+    pub class_index: FPackageIndex,
+    pub super_index: FPackageIndex,
+    #[ue(ue4 = "TemplateIndex_IN_COOKED_EXPORTS")]
+    pub template_index: FPackageIndex,
+    pub outer_index: FPackageIndex,
+    #[ue(with = "FAssetArchive::read_fname")]
+    pub object_name: FName,
+    pub object_flags: u32,
+    #[ue(with = "read_serial_size")]
+    pub serial_size: i64,
+    #[ue(with = "read_serial_size")]
+    pub serial_offset: i64,
+    pub forced_export: bool,
+    pub not_for_client: bool,
+    pub not_for_server: bool,
+    #[ue(before_ue5 = "REMOVE_OBJECT_EXPORT_PACKAGE_GUID")]
+    pub package_guid: FGuid,
+    #[ue(ue5 = "TRACK_OBJECT_EXPORT_IS_INHERITED")]
+    pub is_inherited_instance: bool,
+    pub package_flags: u32,
+    #[ue(ue4 = "LOAD_FOR_EDITOR_GAME")]
+    pub not_always_loaded_for_editor_game: bool,
+    #[ue(ue4 = "COOKED_ASSETS_IN_EDITOR_SUPPORT")]
+    pub is_asset: bool,
+    #[ue(ue5 = "OPTIONAL_RESOURCES")]
+    pub generate_public_hash: bool,
+    #[ue(ue4 = "PRELOAD_DEPENDENCIES_IN_COOKED_EXPORTS", default = "-1")]
+    pub first_export_dependency: i32,
+    #[ue(ue4 = "PRELOAD_DEPENDENCIES_IN_COOKED_EXPORTS")]
+    pub serialization_before_serialization_dependencies: i32,
+    #[ue(ue4 = "PRELOAD_DEPENDENCIES_IN_COOKED_EXPORTS")]
+    pub create_before_serialization_dependencies: i32,
+    #[ue(ue4 = "PRELOAD_DEPENDENCIES_IN_COOKED_EXPORTS")]
+    pub serialization_before_create_dependencies: i32,
+    #[ue(ue4 = "PRELOAD_DEPENDENCIES_IN_COOKED_EXPORTS")]
+    pub create_before_create_dependencies: i32,
+    #[ue(ue5 = "SCRIPT_SERIALIZATION_OFFSET")]
+    pub script_serialization_start_offset: i64,
+    #[ue(ue5 = "SCRIPT_SERIALIZATION_OFFSET")]
+    pub script_serialization_end_offset: i64,
+}
+
+// Serial sizes and offsets were widened to 64 bits
+fn read_serial_size(archive: &mut FAssetArchive) -> io::Result<i64> {
+    if archive.ue4_at_least(EUnrealEngineObjectUE4Version::E64BIT_EXPORTMAP_SERIALSIZES) {
+        archive.read_i64()
+    } else {
+        Ok(archive.read_i32()? as i64)
+    }
+}
@@ -0,0 +1,25 @@
+use super::FPackageIndex;
+use crate::assets::reader::FAssetArchive;
+use crate::objects::FName;
+use crate::readers::UeRead;
+
+#[derive(Debug, Clone, UeRead)]
+#[ue(archive)]
+pub struct FObjectImport {
+    #[ue(with = "FAssetArchive::read_fname")]
+    pub class_package: FName,
+    #[ue(with = "FAssetArchive::read_fname")]
+    pub class_name: FName,
+    pub outer_index: FPackageIndex,
+    #[ue(with = "FAssetArchive::read_fname")]
+    pub object_name: FName,
+    /// Only in uncooked packages.
+    #[ue(
+        ue4 = "NON_OUTER_PACKAGE_IMPORT",
+        if = "!reader.is_filter_editor_only()",
+        with = "FAssetArchive::read_fname"
+    )]
+    pub package_name: Option<FName>,
+    #[ue(ue5 = "OPTIONAL_RESOURCES")]
+    pub import_optional: bool,
+}
@@ -0,0 +1,37 @@
+use crate::readers::UeRead;
+
+/// Reference into a package's tables. Negative values are imports, positive values are exports
+/// and zero is null.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, UeRead)]
+pub struct FPackageIndex {
+    pub index: i32,
+}
+impl FPackageIndex {
+    pub fn new(index: i32) -> Self {
+        Self { index }
+    }
+    pub fn is_null(&self) -> bool {
+        self.index == 0
+    }
+    pub fn is_import(&self) -> bool {
+        self.index < 0
+    }
+    pub fn is_export(&self) -> bool {
+        self.index > 0
+    }
+    pub fn to_import(&self) -> Option<usize> {
+        if self.is_import() {
+            // The index comes straight from the file, so `i32::MIN` has no import
+            Some(self.index.checked_neg()?.checked_sub(1)? as usize)
+        } else {
+            None
+        }
+    }
+    pub fn to_export(&self) -> Option<usize> {
+        if self.is_export() {
+            Some((self.index - 1) as usize)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,7 @@
+mod fobjectexport;
+mod fobjectimport;
+mod fpackageindex;
+
+pub use fobjectexport::*;
+pub use fobjectimport::*;
+pub use fpackageindex::*;
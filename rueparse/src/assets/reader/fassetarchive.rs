@@ -0,0 +1,330 @@
+use std::io;
+
+use crate::assets::objects::{FObjectExport, FObjectImport, FPackageIndex};
+use crate::errors::{self, Error};
+use crate::models::FGuid;
+use crate::objects::FName;
+use crate::objects::core::serialization::FCustomVersionContainer;
+use crate::readers::{Endianness, Reader, ReaderLimits};
+use crate::versions::{
+    EGame, EUnrealEngineObjectUE4Version, EUnrealEngineObjectUE5Version, FPackageFileVersion,
+    VersionContainer,
+};
+
+/// Reader for package data that carries the engine versions, custom versions, name map and
+/// import/export tables, so serializers can query them instead of having them passed in.
+pub struct FAssetArchive<'a> {
+    inner: &'a mut dyn Reader,
+    game: EGame,
+    pub ver: FPackageFileVersion,
+    pub versions: VersionContainer,
+    /// Custom versions from the package summary, looked up before the ones in `versions`.
+    pub custom_versions: FCustomVersionContainer,
+    /// `PackageFlags` from the package summary.
+    pub package_flags: u32,
+    pub name_map: Vec<String>,
+    pub import_map: Vec<FObjectImport>,
+    pub export_map: Vec<FObjectExport>,
+}
+
+impl<'a> FAssetArchive<'a> {
+    /// `PKG_FilterEditorOnly`, set on cooked packages.
+    pub const PKG_FILTER_EDITOR_ONLY: u32 = 0x8000_0000;
+
+    pub fn new(inner: &'a mut dyn Reader, mut versions: VersionContainer) -> Self {
+        let game = *versions.get_game();
+        let ver = versions.get_ver();
+        let ver = FPackageFileVersion::new(ver.file_version_ue4, ver.file_version_ue5);
+        Self {
+            inner,
+            game,
+            ver,
+            versions,
+            custom_versions: FCustomVersionContainer::default(),
+            package_flags: 0,
+            name_map: Vec::new(),
+            import_map: Vec::new(),
+            export_map: Vec::new(),
+        }
+    }
+
+    pub fn game(&self) -> EGame {
+        self.game
+    }
+
+    pub fn ue4_version(&self) -> i32 {
+        self.ver.file_version_ue4
+    }
+
+    pub fn ue5_version(&self) -> i32 {
+        self.ver.file_version_ue5
+    }
+
+    pub fn ue4_at_least(&self, version: EUnrealEngineObjectUE4Version) -> bool {
+        self.ver.file_version_ue4 >= version as i32
+    }
+
+    pub fn ue5_at_least(&self, version: EUnrealEngineObjectUE5Version) -> bool {
+        self.ver.file_version_ue5 >= version as i32
+    }
+
+    /// Whether editor only data was stripped, which is the case for cooked packages.
+    pub fn is_filter_editor_only(&self) -> bool {
+        self.package_flags & Self::PKG_FILTER_EDITOR_ONLY != 0
+    }
+
+    pub fn custom_version(&self, key: &FGuid) -> Option<i32> {
+        match self.custom_versions.get_version(key) {
+            Some(v) => Some(v),
+            None => match &self.versions.custom_versions {
+                Some(c) => c.get_version(key),
+                None => None,
+            },
+        }
+    }
+
+    /// Game specific serialization switch from the `VersionContainer`, false when unset.
+    pub fn option(&self, name: &str) -> bool {
+        self.versions.options.get(name).copied().unwrap_or(false)
+    }
+
+    pub fn name(&self, index: i32) -> Option<&str> {
+        if index < 0 {
+            return None;
+        }
+        self.name_map.get(index as usize).map(|n| n.as_str())
+    }
+
+    /// Formats an `FName` the way the engine does, with a non-zero number appended as `_{number - 1}`.
+    pub fn fname_to_string(&self, name: &FName) -> Option<String> {
+        let base = self.name(name.index)?;
+        if name.number == 0 {
+            Some(base.to_string())
+        } else {
+            Some(format!("{}_{}", base, name.number - 1))
+        }
+    }
+
+    /// Reads an `FName` and checks its index against the name map.
+    pub fn read_fname(&mut self) -> io::Result<FName> {
+        let index = self.read_i32()?;
+        let number = self.read_i32()?;
+        if self.name(index).is_none() {
+            let offset = self.position()?.saturating_sub(8);
+            return Err(Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "FName index {} out of range of {} names",
+                    index,
+                    self.name_map.len()
+                ),
+            )
+            .at(offset)
+            .into());
+        }
+        Ok(FName { index, number })
+    }
+
+    pub fn import(&self, index: FPackageIndex) -> Option<&FObjectImport> {
+        self.import_map.get(index.to_import()?)
+    }
+
+    pub fn export(&self, index: FPackageIndex) -> Option<&FObjectExport> {
+        self.export_map.get(index.to_export()?)
+    }
+
+    /// Name of the import or export an index points to.
+    pub fn object_name(&self, index: FPackageIndex) -> Option<String> {
+        if let Some(import) = self.import(index) {
+            return self.fname_to_string(&import.object_name);
+        }
+        self.fname_to_string(&self.export(index)?.object_name)
+    }
+
+    /// Reads `count` serialized name entries at the current position into the name map.
+    pub fn read_name_map(&mut self, count: usize) -> io::Result<()> {
+        self.read_name_entries(count)
+            .map_err(|e| errors::within(e, "NameMap"))
+    }
+
+    fn read_name_entries(&mut self, count: usize) -> io::Result<()> {
+        // Every entry has at least its length
+        self.check_allocation(count as u64, 4)?;
+        let has_hashes = self.ue4_at_least(EUnrealEngineObjectUE4Version::NAME_HASHES_SERIALIZED);
+        let mut name_map = Vec::with_capacity(count);
+        for i in 0..count {
+            let name = match self.read_fstring() {
+                Ok(n) => n,
+                Err(e) => return Err(errors::at_index(e, i)),
+            };
+            name_map.push(name);
+            if has_hashes {
+                // Case insensitive and case preserving hashes
+                self.skip(4)?;
+            }
+        }
+        self.name_map = name_map;
+        Ok(())
+    }
+
+    /// Reads `count` imports at the current position. The name map must be loaded first.
+    pub fn read_import_map(&mut self, count: usize) -> io::Result<()> {
+        // Three names and an outer index
+        if let Err(e) = self.check_allocation(count as u64, 28) {
+            return Err(errors::within(e, "ImportMap"));
+        }
+        let mut import_map = Vec::with_capacity(count);
+        for i in 0..count {
+            match FObjectImport::from_archive(self) {
+                Ok(import) => import_map.push(import),
+                Err(e) => return Err(errors::within(errors::at_index(e, i), "ImportMap")),
+            }
+        }
+        self.import_map = import_map;
+        Ok(())
+    }
+
+    /// Reads `count` exports at the current position. The name map must be loaded first.
+    pub fn read_export_map(&mut self, count: usize) -> io::Result<()> {
+        // The smallest layout, from the oldest loadable version
+        if let Err(e) = self.check_allocation(count as u64, 64) {
+            return Err(errors::within(e, "ExportMap"));
+        }
+        let mut export_map = Vec::with_capacity(count);
+        for i in 0..count {
+            match FObjectExport::from_archive(self) {
+                Ok(export) => export_map.push(export),
+                Err(e) => return Err(errors::within(errors::at_index(e, i), "ExportMap")),
+            }
+        }
+        self.export_map = export_map;
+        Ok(())
+    }
+}
+
+impl<'a> Reader for FAssetArchive<'a> {
+    fn read_u8(&mut self) -> io::Result<u8> {
+        self.inner.read_u8()
+    }
+
+    fn read_u16(&mut self) -> io::Result<u16> {
+        self.inner.read_u16()
+    }
+
+    fn read_u32(&mut self) -> io::Result<u32> {
+        self.inner.read_u32()
+    }
+
+    fn read_i32(&mut self) -> io::Result<i32> {
+        self.inner.read_i32()
+    }
+
+    fn read_u64(&mut self) -> io::Result<u64> {
+        self.inner.read_u64()
+    }
+
+    fn read_i64(&mut self) -> io::Result<i64> {
+        self.inner.read_i64()
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        self.inner.read_exact(buf)
+    }
+
+    fn seek(&mut self, pos: u64) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+
+    fn position(&mut self) -> io::Result<u64> {
+        self.inner.position()
+    }
+
+    fn len(&mut self) -> io::Result<u64> {
+        self.inner.len()
+    }
+
+    fn limits(&self) -> ReaderLimits {
+        self.inner.limits()
+    }
+
+    fn endianness(&self) -> Endianness {
+        self.inner.endianness()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FAssetArchive;
+    use crate::assets::objects::FPackageIndex;
+    use crate::readers::FileReader;
+    use crate::versions::{EUnrealEngineObjectUE4Version, FPackageFileVersion, VersionContainer};
+    use std::io::Cursor;
+
+    fn fstring(data: &mut Vec<u8>, value: &str) {
+        data.extend((value.len() as i32 + 1).to_le_bytes());
+        data.extend(value.as_bytes());
+        data.push(0);
+    }
+
+    fn fname(data: &mut Vec<u8>, index: i32, number: i32) {
+        data.extend(index.to_le_bytes());
+        data.extend(number.to_le_bytes());
+    }
+
+    #[test]
+    fn reads_tables_and_resolves_names() {
+        let mut data = Vec::new();
+        for name in ["/Script/CoreUObject", "Class", "Texture2D"] {
+            fstring(&mut data, name);
+            data.extend([0u8; 4]);
+        }
+        // Cooked import, without a package name
+        fname(&mut data, 0, 0);
+        fname(&mut data, 1, 0);
+        data.extend(0i32.to_le_bytes());
+        fname(&mut data, 2, 3);
+        // Uncooked import, with a package name
+        fname(&mut data, 0, 0);
+        fname(&mut data, 1, 0);
+        data.extend(0i32.to_le_bytes());
+        fname(&mut data, 2, 0);
+        fname(&mut data, 0, 0);
+        // Out of range name index
+        fname(&mut data, 7, 0);
+
+        let version = EUnrealEngineObjectUE4Version::NON_OUTER_PACKAGE_IMPORT as i32;
+        let versions = VersionContainer::new(
+            None,
+            None,
+            Some(FPackageFileVersion::create_ue4_version(version)),
+            None,
+            None,
+            None,
+        );
+        let mut reader = FileReader::new(Cursor::new(data));
+        let mut archive = FAssetArchive::new(&mut reader, versions);
+        assert!(archive.ue4_at_least(EUnrealEngineObjectUE4Version::NAME_HASHES_SERIALIZED));
+
+        archive.read_name_map(3).unwrap();
+        archive.package_flags = FAssetArchive::PKG_FILTER_EDITOR_ONLY;
+        archive.read_import_map(1).unwrap();
+        let import = archive.import(FPackageIndex::new(-1)).unwrap();
+        assert!(import.package_name.is_none());
+        assert_eq!(
+            archive.object_name(FPackageIndex::new(-1)).as_deref(),
+            Some("Texture2D_2")
+        );
+        assert!(archive.export(FPackageIndex::new(1)).is_none());
+        assert!(archive.import(FPackageIndex::new(i32::MIN)).is_none());
+
+        archive.package_flags = 0;
+        archive.read_import_map(1).unwrap();
+        let import = archive.import(FPackageIndex::new(-1)).unwrap();
+        assert!(import.package_name.is_some());
+        assert_eq!(
+            archive.object_name(FPackageIndex::new(-1)).as_deref(),
+            Some("Texture2D")
+        );
+        assert!(archive.read_fname().is_err());
+    }
+}
@@ -0,0 +1,3 @@
+mod fassetarchive;
+
+pub use fassetarchive::*;
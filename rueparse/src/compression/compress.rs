@@ -0,0 +1,217 @@
+use std::io::{self, Write};
+
+use flate2::write::{GzEncoder, ZlibEncoder};
+use oodle::{Oodle, OodleCompressionLevel, OodleCompressor};
+
+use super::CompressionMethod;
+
+#[derive(Debug)]
+pub enum CompressionError {
+    UnsupportedMethod(CompressionMethod),
+    OodleNotFound,
+    OodleFailed,
+    WriteError(io::Error),
+}
+
+/// Mapped onto each codec's own level range. `Custom` is passed through as the codec's native level.
+/// LZ4 has a single level and ignores it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionLevel {
+    Fastest,
+    Fast,
+    Default,
+    Best,
+    Custom(i32),
+}
+
+impl CompressionLevel {
+    fn flate2(self) -> flate2::Compression {
+        match self {
+            CompressionLevel::Fastest => flate2::Compression::new(1),
+            CompressionLevel::Fast => flate2::Compression::new(3),
+            CompressionLevel::Default => flate2::Compression::new(6),
+            CompressionLevel::Best => flate2::Compression::new(9),
+            CompressionLevel::Custom(l) => flate2::Compression::new(l.clamp(0, 9) as u32),
+        }
+    }
+
+    fn zstd(self) -> i32 {
+        match self {
+            CompressionLevel::Fastest => 1,
+            CompressionLevel::Fast => 2,
+            CompressionLevel::Default => 3,
+            CompressionLevel::Best => 19,
+            CompressionLevel::Custom(l) => l,
+        }
+    }
+
+    fn brotli(self) -> u32 {
+        match self {
+            CompressionLevel::Fastest => 1,
+            CompressionLevel::Fast => 4,
+            CompressionLevel::Default => 9,
+            CompressionLevel::Best => 11,
+            CompressionLevel::Custom(l) => l.clamp(0, 11) as u32,
+        }
+    }
+
+    fn oodle(self) -> OodleCompressionLevel {
+        match self {
+            CompressionLevel::Fastest => OodleCompressionLevel::SuperFast,
+            CompressionLevel::Fast => OodleCompressionLevel::Fast,
+            CompressionLevel::Default => OodleCompressionLevel::Normal,
+            CompressionLevel::Best => OodleCompressionLevel::Optimal5,
+            CompressionLevel::Custom(l) => match l.clamp(-4, 9) {
+                -4 => OodleCompressionLevel::HyperFast4,
+                -3 => OodleCompressionLevel::HyperFast3,
+                -2 => OodleCompressionLevel::HyperFast2,
+                -1 => OodleCompressionLevel::HyperFast1,
+                0 => OodleCompressionLevel::None,
+                1 => OodleCompressionLevel::SuperFast,
+                2 => OodleCompressionLevel::VeryFast,
+                3 => OodleCompressionLevel::Fast,
+                4 => OodleCompressionLevel::Normal,
+                5 => OodleCompressionLevel::Optimal1,
+                6 => OodleCompressionLevel::Optimal2,
+                7 => OodleCompressionLevel::Optimal3,
+                8 => OodleCompressionLevel::Optimal4,
+                _ => OodleCompressionLevel::Optimal5,
+            },
+        }
+    }
+}
+
+fn compress_stream<W: Write>(
+    mut encoder: W,
+    input: &[u8],
+    finish: impl FnOnce(W) -> io::Result<Vec<u8>>,
+) -> Result<Vec<u8>, CompressionError> {
+    if let Err(e) = encoder.write_all(input) {
+        return Err(CompressionError::WriteError(e));
+    }
+    match finish(encoder) {
+        Ok(v) => Ok(v),
+        Err(e) => Err(CompressionError::WriteError(e)),
+    }
+}
+
+/// Compresses `input` with the given method. Oodle data is compressed with Kraken
+/// and needs the native library, use `compress_oodle` to pick another compressor.
+pub fn compress(
+    method: CompressionMethod,
+    input: &[u8],
+    level: CompressionLevel,
+    oodle: Option<&Oodle>,
+) -> Result<Vec<u8>, CompressionError> {
+    match method {
+        CompressionMethod::None => Ok(input.to_vec()),
+        CompressionMethod::Zlib => {
+            compress_stream(ZlibEncoder::new(Vec::new(), level.flate2()), input, |e| {
+                e.finish()
+            })
+        }
+        CompressionMethod::Gzip => {
+            compress_stream(GzEncoder::new(Vec::new(), level.flate2()), input, |e| {
+                e.finish()
+            })
+        }
+        CompressionMethod::Brotli => compress_stream(
+            brotli::CompressorWriter::new(Vec::new(), 4096, level.brotli(), 22),
+            input,
+            |e| Ok(e.into_inner()),
+        ),
+        CompressionMethod::LZ4 => Ok(lz4_flex::block::compress(input)),
+        CompressionMethod::Zstd => match zstd::bulk::compress(input, level.zstd()) {
+            Ok(v) => Ok(v),
+            Err(e) => Err(CompressionError::WriteError(e)),
+        },
+        CompressionMethod::Oodle => match oodle {
+            Some(o) => compress_oodle(input, OodleCompressor::Kraken, level, o),
+            None => Err(CompressionError::OodleNotFound),
+        },
+        CompressionMethod::Custom | CompressionMethod::Unknown => {
+            Err(CompressionError::UnsupportedMethod(method))
+        }
+    }
+}
+
+pub fn compress_oodle(
+    input: &[u8],
+    compressor: OodleCompressor,
+    level: CompressionLevel,
+    oodle: &Oodle,
+) -> Result<Vec<u8>, CompressionError> {
+    // OodleCompressor is neither Copy nor Clone and both calls take it by value
+    let size_compressor = match &compressor {
+        OodleCompressor::Kraken => OodleCompressor::Kraken,
+        OodleCompressor::Leviathan => OodleCompressor::Leviathan,
+        OodleCompressor::Mermaid => OodleCompressor::Mermaid,
+        OodleCompressor::Selkie => OodleCompressor::Selkie,
+        OodleCompressor::Hydra => OodleCompressor::Hydra,
+    };
+    let mut output =
+        vec![0u8; oodle.get_compressed_buffer_size_needed(size_compressor, input.len())];
+    let size = oodle.compress(compressor, level.oodle(), input, &mut output);
+    if size == 0 && !input.is_empty() {
+        return Err(CompressionError::OodleFailed);
+    }
+    output.truncate(size);
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CompressionLevel, compress};
+    use crate::compression::{CompressionMethod, decompress};
+
+    #[test]
+    fn round_trips_through_decompress() {
+        let data: Vec<u8> = (0..200_000u32).map(|i| (i % 97 + i / 1000) as u8).collect();
+        for method in [
+            CompressionMethod::None,
+            CompressionMethod::Zlib,
+            CompressionMethod::Gzip,
+            CompressionMethod::Brotli,
+            CompressionMethod::LZ4,
+            CompressionMethod::Zstd,
+        ] {
+            for level in [
+                CompressionLevel::Fastest,
+                CompressionLevel::Default,
+                CompressionLevel::Best,
+            ] {
+                let compressed = compress(method, &data, level, None).unwrap();
+                let mut output = vec![0u8; data.len()];
+                decompress(method, &compressed, &mut output, None).unwrap();
+                assert_eq!(output, data, "{:?} {:?}", method, level);
+            }
+        }
+    }
+
+    // Set OODLE_LIB_PATH to run against the native library
+    #[test]
+    fn oodle_round_trip() {
+        let path = match std::env::var("OODLE_LIB_PATH") {
+            Ok(p) => p,
+            Err(_) => return,
+        };
+        let oodle = oodle::Oodle::load(&path).unwrap();
+        let data: Vec<u8> = (0..200_000u32).map(|i| (i % 97) as u8).collect();
+        let compressed = compress(
+            CompressionMethod::Oodle,
+            &data,
+            CompressionLevel::Default,
+            Some(&oodle),
+        )
+        .unwrap();
+        let mut output = vec![0u8; data.len()];
+        decompress(
+            CompressionMethod::Oodle,
+            &compressed,
+            &mut output,
+            Some(&oodle),
+        )
+        .unwrap();
+        assert_eq!(output, data);
+    }
+}
@@ -0,0 +1,211 @@
+use std::io::{self, Cursor, Read};
+
+use flate2::read::{GzDecoder, ZlibDecoder};
+use oodle::Oodle;
+
+use super::CompressionMethod;
+
+#[derive(Debug)]
+pub enum DecompressionError {
+    UnsupportedMethod(CompressionMethod),
+    UnknownMethod(String),
+    /// Oodle data with neither the native library nor a custom "Oodle" decompressor.
+    OodleNotFound,
+    SizeMismatch { expected: usize, actual: usize },
+    ReadError(io::Error),
+}
+
+/// Reads the stream until `output` is full and fails if it holds more or less data than that.
+fn decompress_stream(mut stream: impl Read, output: &mut [u8]) -> Result<(), DecompressionError> {
+    let mut written = 0;
+    while written < output.len() {
+        match stream.read(&mut output[written..]) {
+            Ok(0) => break,
+            Ok(n) => written += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(DecompressionError::ReadError(e)),
+        }
+    }
+    if written == output.len() {
+        let mut extra = [0u8; 1];
+        match stream.read(&mut extra) {
+            Ok(0) => {}
+            Ok(_) => written += 1,
+            Err(e) => return Err(DecompressionError::ReadError(e)),
+        }
+    }
+    check_size(output.len(), written)
+}
+
+fn check_size(expected: usize, actual: usize) -> Result<(), DecompressionError> {
+    if expected != actual {
+        return Err(DecompressionError::SizeMismatch { expected, actual });
+    }
+    Ok(())
+}
+
+/// Decompresses `input` into `output`, which must be sized to the exact uncompressed length.
+/// `oodle` is only needed for Oodle compressed data.
+pub fn decompress(
+    method: CompressionMethod,
+    input: &[u8],
+    output: &mut [u8],
+    oodle: Option<&Oodle>,
+) -> Result<(), DecompressionError> {
+    match method {
+        CompressionMethod::None => {
+            check_size(output.len(), input.len())?;
+            output.copy_from_slice(input);
+            Ok(())
+        }
+        CompressionMethod::Zlib => decompress_stream(ZlibDecoder::new(input), output),
+        CompressionMethod::Gzip => decompress_stream(GzDecoder::new(input), output),
+        CompressionMethod::Brotli => {
+            decompress_stream(brotli::Decompressor::new(Cursor::new(input), 4096), output)
+        }
+        CompressionMethod::LZ4 => match lz4_flex::block::decompress_into(input, output) {
+            Ok(n) => check_size(output.len(), n),
+            Err(e) => Err(DecompressionError::ReadError(io::Error::new(
+                io::ErrorKind::InvalidData,
+                e.to_string(),
+            ))),
+        },
+        CompressionMethod::Zstd => match zstd::stream::read::Decoder::with_buffer(input) {
+            Ok(decoder) => decompress_stream(decoder, output),
+            Err(e) => Err(DecompressionError::ReadError(e)),
+        },
+        CompressionMethod::Oodle => match oodle {
+            // Oodle returns 0 on failure, otherwise the number of bytes written
+            Some(o) => check_size(output.len(), o.decompress(input, output)),
+            None => Err(DecompressionError::OodleNotFound),
+        },
+        CompressionMethod::Custom | CompressionMethod::Unknown => {
+            Err(DecompressionError::UnsupportedMethod(method))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DecompressionError, decompress};
+    use crate::compression::{CompressionLevel, CompressionMethod, compress};
+    use std::process::Command;
+
+    fn size_mismatch(
+        method: CompressionMethod,
+        input: &[u8],
+        output_len: usize,
+        oodle: Option<&oodle::Oodle>,
+    ) -> Option<(usize, usize)> {
+        let mut output = vec![0u8; output_len];
+        match decompress(method, input, &mut output, oodle) {
+            Err(DecompressionError::SizeMismatch { expected, actual }) => Some((expected, actual)),
+            Ok(()) => None,
+            Err(e) => panic!("{:?}: unexpected {:?}", method, e),
+        }
+    }
+
+    #[test]
+    fn output_size_must_match_exactly() {
+        let data: Vec<u8> = (0..5000u32).map(|i| (i % 13) as u8).collect();
+        for method in [
+            CompressionMethod::None,
+            CompressionMethod::Zlib,
+            CompressionMethod::Gzip,
+            CompressionMethod::Brotli,
+            CompressionMethod::Zstd,
+        ] {
+            let compressed = compress(method, &data, CompressionLevel::Default, None).unwrap();
+            assert_eq!(size_mismatch(method, &compressed, data.len(), None), None);
+            // Streams report the bytes they produced, or one more than fits
+            assert_eq!(
+                size_mismatch(method, &compressed, data.len() + 1, None),
+                Some((data.len() + 1, data.len())),
+                "{:?}",
+                method
+            );
+            assert_eq!(
+                size_mismatch(method, &compressed, data.len() - 1, None),
+                Some((data.len() - 1, data.len())),
+                "{:?}",
+                method
+            );
+        }
+
+        // LZ4 blocks fail on their own when the output is too small
+        let compressed = lz4_flex::block::compress(&data);
+        assert_eq!(
+            size_mismatch(CompressionMethod::LZ4, &compressed, data.len() + 1, None),
+            Some((data.len() + 1, data.len()))
+        );
+        let mut output = vec![0u8; data.len() - 1];
+        assert!(matches!(
+            decompress(CompressionMethod::LZ4, &compressed, &mut output, None),
+            Err(DecompressionError::ReadError(_))
+        ));
+    }
+
+    // A stand-in library whose decoder copies the input and reports how much it copied
+    const FAKE_OODLE: &str = r#"
+        use std::ffi::c_void;
+
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn OodleLZ_Decompress(
+            input: *const u8, input_size: usize, output: *mut u8, output_size: usize,
+            _: i32, _: i32, _: i32, _: *mut c_void, _: usize, _: *mut c_void, _: *mut c_void,
+            _: *mut c_void, _: usize, _: i32, _: usize,
+        ) -> usize {
+            let size = input_size.min(output_size);
+            unsafe { std::ptr::copy_nonoverlapping(input, output, size) };
+            size
+        }
+
+        #[unsafe(no_mangle)]
+        pub extern "C" fn OodleLZ_Compress() -> usize { 0 }
+
+        #[unsafe(no_mangle)]
+        pub extern "C" fn OodleLZ_GetCompressedBufferSizeNeeded() -> usize { 0 }
+    "#;
+
+    #[test]
+    fn oodle_must_fill_the_output() {
+        let dir = std::env::temp_dir().join(format!("rueparse-oodle-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("fake_oodle.rs");
+        std::fs::write(&source, FAKE_OODLE).unwrap();
+        let library = dir.join(format!(
+            "{}fake_oodle{}",
+            std::env::consts::DLL_PREFIX,
+            std::env::consts::DLL_SUFFIX
+        ));
+        let output = Command::new("rustc")
+            .args(["--crate-type", "cdylib", "--edition", "2024", "-o"])
+            .arg(&library)
+            .arg(&source)
+            .output()
+            .expect("rustc is needed to build the stand-in Oodle library");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let oodle = oodle::Oodle::load(library.to_str().unwrap()).unwrap();
+
+        let data = [7u8; 64];
+        let oodle = Some(&oodle);
+        assert_eq!(
+            size_mismatch(CompressionMethod::Oodle, &data, 64, oodle),
+            None
+        );
+        assert_eq!(
+            size_mismatch(CompressionMethod::Oodle, &data, 100, oodle),
+            Some((100, 64))
+        );
+        // Failures are reported as zero bytes written
+        assert_eq!(
+            size_mismatch(CompressionMethod::Oodle, &[], 64, oodle),
+            Some((64, 0))
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,3 +1,11 @@
+mod compress;
+mod decompress;
+mod registry;
+
+pub use compress::*;
+pub use decompress::*;
+pub use registry::*;
+
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CompressionMethod {
@@ -9,6 +17,7 @@ pub enum CompressionMethod {
     LZ4 = 5,
     Zstd = 6,
     Unknown = 7,
+    Brotli = 8,
 }
 impl From<u8> for CompressionMethod {
     fn from(value: u8) -> Self {
@@ -20,6 +29,23 @@ impl From<u8> for CompressionMethod {
             4 => CompressionMethod::Oodle,
             5 => CompressionMethod::LZ4,
             6 => CompressionMethod::Zstd,
+            8 => CompressionMethod::Brotli,
+            _ => CompressionMethod::Unknown,
+        }
+    }
+}
+
+impl CompressionMethod {
+    /// Maps the method names stored in IoStore TOCs and pak footers.
+    pub fn from_name(name: &str) -> Self {
+        match name.to_ascii_lowercase().as_str() {
+            "" | "none" => CompressionMethod::None,
+            "zlib" => CompressionMethod::Zlib,
+            "gzip" => CompressionMethod::Gzip,
+            "oodle" => CompressionMethod::Oodle,
+            "lz4" => CompressionMethod::LZ4,
+            "zstd" => CompressionMethod::Zstd,
+            "brotli" => CompressionMethod::Brotli,
             _ => CompressionMethod::Unknown,
         }
     }
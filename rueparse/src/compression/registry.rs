@@ -0,0 +1,152 @@
+use std::collections::{HashMap, HashSet};
+
+use oodle::Oodle;
+
+use super::{CompressionMethod, DecompressionError, decompress};
+
+/// A decompressor for one named compression method. Implement this for proprietary game codecs.
+pub trait Decompressor: Send + Sync {
+    fn decompress(&self, input: &[u8], output: &mut [u8]) -> Result<(), DecompressionError>;
+}
+
+pub struct BuiltinDecompressor {
+    method: CompressionMethod,
+    oodle: Option<Oodle>,
+}
+
+impl BuiltinDecompressor {
+    pub fn new(method: CompressionMethod, oodle: Option<Oodle>) -> Self {
+        Self { method, oodle }
+    }
+}
+
+impl Decompressor for BuiltinDecompressor {
+    fn decompress(&self, input: &[u8], output: &mut [u8]) -> Result<(), DecompressionError> {
+        decompress(self.method, input, output, self.oodle.as_ref())
+    }
+}
+
+/// Decompressors looked up by the method names IoStore TOCs and pak footers store.
+/// Names are matched case-insensitively.
+pub struct CompressionRegistry {
+    decompressors: HashMap<String, Box<dyn Decompressor>>,
+    // Names still served by a `BuiltinDecompressor`
+    builtins: HashSet<String>,
+}
+
+impl CompressionRegistry {
+    pub fn new(oodle: Option<Oodle>) -> Self {
+        let mut registry = Self {
+            decompressors: HashMap::new(),
+            builtins: HashSet::new(),
+        };
+        for (name, method) in [
+            ("None", CompressionMethod::None),
+            ("Zlib", CompressionMethod::Zlib),
+            ("Gzip", CompressionMethod::Gzip),
+            ("LZ4", CompressionMethod::LZ4),
+            ("Zstd", CompressionMethod::Zstd),
+            ("Brotli", CompressionMethod::Brotli),
+        ] {
+            registry.register_builtin(name, method, None);
+        }
+        registry.set_oodle(oodle);
+        registry
+    }
+
+    fn register_builtin(&mut self, name: &str, method: CompressionMethod, oodle: Option<Oodle>) {
+        let name = name.to_ascii_lowercase();
+        self.decompressors.insert(
+            name.clone(),
+            Box::new(BuiltinDecompressor::new(method, oodle)),
+        );
+        self.builtins.insert(name);
+    }
+
+    /// Updates the built-in Oodle decompressor. A custom one registered as "Oodle" takes
+    /// precedence and is kept.
+    pub fn set_oodle(&mut self, oodle: Option<Oodle>) {
+        if self.contains("Oodle") && !self.builtins.contains("oodle") {
+            return;
+        }
+        self.register_builtin("Oodle", CompressionMethod::Oodle, oodle);
+    }
+
+    /// Registers a decompressor, replacing any existing one with the same name.
+    pub fn register(&mut self, name: &str, decompressor: Box<dyn Decompressor>) {
+        let name = name.to_ascii_lowercase();
+        self.builtins.remove(&name);
+        self.decompressors.insert(name, decompressor);
+    }
+
+    pub fn unregister(&mut self, name: &str) -> Option<Box<dyn Decompressor>> {
+        let name = name.to_ascii_lowercase();
+        self.builtins.remove(&name);
+        self.decompressors.remove(&name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Decompressor> {
+        self.decompressors
+            .get(&name.to_ascii_lowercase())
+            .map(|d| d.as_ref())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.decompressors.contains_key(&name.to_ascii_lowercase())
+    }
+
+    pub fn decompress(
+        &self,
+        name: &str,
+        input: &[u8],
+        output: &mut [u8],
+    ) -> Result<(), DecompressionError> {
+        match self.get(name) {
+            Some(d) => d.decompress(input, output),
+            None => Err(DecompressionError::UnknownMethod(name.to_string())),
+        }
+    }
+}
+
+impl Default for CompressionRegistry {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CompressionRegistry, Decompressor};
+    use crate::compression::DecompressionError;
+
+    struct Fill(u8);
+
+    impl Decompressor for Fill {
+        fn decompress(&self, _: &[u8], output: &mut [u8]) -> Result<(), DecompressionError> {
+            output.fill(self.0);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn custom_oodle_survives_set_oodle() {
+        let mut registry = CompressionRegistry::default();
+        registry.register("OODLE", Box::new(Fill(7)));
+        registry.set_oodle(None);
+        let mut output = [0u8; 4];
+        registry.decompress("Oodle", &[], &mut output).unwrap();
+        assert_eq!(output, [7; 4]);
+
+        // Without the custom one the built-in comes back, which needs the library
+        registry.unregister("oodle");
+        registry.set_oodle(None);
+        assert!(matches!(
+            registry.decompress("Oodle", &[], &mut output),
+            Err(DecompressionError::OodleNotFound)
+        ));
+        assert!(matches!(
+            registry.decompress("GameLZ", &[], &mut output),
+            Err(DecompressionError::UnknownMethod(_))
+        ));
+    }
+}
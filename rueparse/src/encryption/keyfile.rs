@@ -0,0 +1,254 @@
+use std::env;
+use std::fs;
+use std::io;
+
+use hex::FromHexError;
+use serde_json::Value;
+
+use crate::models::{FAesKey, FGuid};
+
+const MAIN_KEY_GUID: &str = "00000000000000000000000000000000";
+
+#[derive(Debug)]
+pub enum KeyLoadError {
+    ReadError(io::Error),
+    JsonError(serde_json::Error),
+    MissingField(String),
+    InvalidField(String),
+    MalformedLine(usize),
+    InvalidGuid {
+        location: String,
+        reason: &'static str,
+    },
+    InvalidKey {
+        location: String,
+        reason: FromHexError,
+    },
+}
+
+fn parse_entry(
+    guid: &str,
+    key: &str,
+    guid_location: String,
+    key_location: String,
+) -> Result<(FGuid, FAesKey), KeyLoadError> {
+    let guid = match FGuid::from_hex(guid.trim()) {
+        Ok(g) => g,
+        Err(reason) => {
+            return Err(KeyLoadError::InvalidGuid {
+                location: guid_location,
+                reason,
+            });
+        }
+    };
+    let key = match FAesKey::from_str(key.trim()) {
+        Ok(k) => k,
+        Err(reason) => {
+            return Err(KeyLoadError::InvalidKey {
+                location: key_location,
+                reason,
+            });
+        }
+    };
+    Ok((guid, key))
+}
+
+fn get_str<'a>(value: &'a Value, field: &str, location: &str) -> Result<&'a str, KeyLoadError> {
+    match value.get(field) {
+        Some(Value::String(s)) => Ok(s),
+        Some(_) => Err(KeyLoadError::InvalidField(format!("{}{}", location, field))),
+        None => Err(KeyLoadError::MissingField(format!("{}{}", location, field))),
+    }
+}
+
+/// Parses the `{"mainKey": "...", "dynamicKeys": [{"guid": "...", "key": "..."}]}` layout.
+/// The main key is returned under the zero GUID, both fields are optional.
+pub fn parse_json_keys(json: &str) -> Result<Vec<(FGuid, FAesKey)>, KeyLoadError> {
+    let root: Value = match serde_json::from_str(json) {
+        Ok(v) => v,
+        Err(e) => return Err(KeyLoadError::JsonError(e)),
+    };
+    let mut keys = Vec::new();
+
+    match root.get("mainKey") {
+        Some(Value::String(key)) if !key.is_empty() => {
+            keys.push(parse_entry(
+                MAIN_KEY_GUID,
+                key,
+                String::from("mainKey"),
+                String::from("mainKey"),
+            )?);
+        }
+        Some(Value::String(_)) | Some(Value::Null) | None => {}
+        Some(_) => return Err(KeyLoadError::InvalidField(String::from("mainKey"))),
+    }
+
+    match root.get("dynamicKeys") {
+        Some(Value::Array(entries)) => {
+            for (i, entry) in entries.iter().enumerate() {
+                let location = format!("dynamicKeys[{}].", i);
+                let guid = get_str(entry, "guid", &location)?;
+                let key = get_str(entry, "key", &location)?;
+                keys.push(parse_entry(
+                    guid,
+                    key,
+                    format!("{}guid", location),
+                    format!("{}key", location),
+                )?);
+            }
+        }
+        Some(Value::Null) | None => {}
+        Some(_) => return Err(KeyLoadError::InvalidField(String::from("dynamicKeys"))),
+    }
+    Ok(keys)
+}
+
+/// Parses a plain text list with one `GUID:key` pair per line.
+/// Empty lines and lines starting with `#` are skipped.
+pub fn parse_text_keys(text: &str) -> Result<Vec<(FGuid, FAesKey)>, KeyLoadError> {
+    let mut keys = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line_number = i + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (guid, key) = match line.split_once(':') {
+            Some(pair) => pair,
+            None => return Err(KeyLoadError::MalformedLine(line_number)),
+        };
+        let location = format!("line {}", line_number);
+        keys.push(parse_entry(guid, key, location.clone(), location)?);
+    }
+    Ok(keys)
+}
+
+/// Reads the main key from the `prefix` variable and dynamic keys from `prefix_<GUID>` variables.
+/// Variables that are not valid UTF-8, or whose suffix is not a GUID, belong to something else
+/// and are skipped.
+pub fn parse_env_keys(prefix: &str) -> Result<Vec<(FGuid, FAesKey)>, KeyLoadError> {
+    let mut keys = Vec::new();
+    let dynamic_prefix = format!("{}_", prefix);
+    for (name, value) in env::vars_os() {
+        let (name, value) = match (name.into_string(), value.into_string()) {
+            (Ok(name), Ok(value)) => (name, value),
+            _ => continue,
+        };
+        if name == prefix {
+            keys.push(parse_entry(MAIN_KEY_GUID, &value, name.clone(), name)?);
+        } else if let Some(guid) = name.strip_prefix(&dynamic_prefix) {
+            if FGuid::from_hex(guid).is_err() {
+                continue;
+            }
+            keys.push(parse_entry(guid, &value, name.clone(), name.clone())?);
+        }
+    }
+    Ok(keys)
+}
+
+pub fn read_json_keys(path: &str) -> Result<Vec<(FGuid, FAesKey)>, KeyLoadError> {
+    match fs::read_to_string(path) {
+        Ok(s) => parse_json_keys(&s),
+        Err(e) => Err(KeyLoadError::ReadError(e)),
+    }
+}
+
+pub fn read_text_keys(path: &str) -> Result<Vec<(FGuid, FAesKey)>, KeyLoadError> {
+    match fs::read_to_string(path) {
+        Ok(s) => parse_text_keys(&s),
+        Err(e) => Err(KeyLoadError::ReadError(e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{KeyLoadError, parse_env_keys, parse_json_keys, parse_text_keys};
+    use crate::models::FGuid;
+
+    const GUID: &str = "0123456789ABCDEF0123456789ABCDEF";
+
+    #[test]
+    fn loads_json_keys() {
+        let json = format!(
+            r#"{{"mainKey": "0x0102", "dynamicKeys": [{{"guid": "{}", "key": "aabb"}}]}}"#,
+            GUID
+        );
+        let keys = parse_json_keys(&json).unwrap();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0].0, FGuid::default());
+        assert_eq!(keys[1].0, FGuid::from_hex(GUID).unwrap());
+        assert_eq!(keys[1].1.key_string, "0xaabb");
+
+        let json = r#"{"dynamicKeys": [{"guid": "xyz", "key": "aa"}]}"#;
+        match parse_json_keys(json) {
+            Err(KeyLoadError::InvalidGuid { location, .. }) => {
+                assert_eq!(location, "dynamicKeys[0].guid")
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        let json = r#"{"dynamicKeys": [{"guid": "xyz"}]}"#;
+        match parse_json_keys(json) {
+            Err(KeyLoadError::MissingField(field)) => assert_eq!(field, "dynamicKeys[0].key"),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(matches!(
+            parse_json_keys(r#"{"mainKey": 1}"#),
+            Err(KeyLoadError::InvalidField(_))
+        ));
+    }
+
+    #[test]
+    fn loads_text_keys_with_line_numbers() {
+        let text = format!("# comment\n\n{}:0xaa\n", GUID);
+        let keys = parse_text_keys(&text).unwrap();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].1.key_string, "0xaa");
+
+        let text = format!("{}:0xaa\nno separator", GUID);
+        assert!(matches!(
+            parse_text_keys(&text),
+            Err(KeyLoadError::MalformedLine(2))
+        ));
+        let text = format!("# comment\n{}:0xzz", GUID);
+        match parse_text_keys(&text) {
+            Err(KeyLoadError::InvalidKey { location, .. }) => assert_eq!(location, "line 2"),
+            other => panic!("unexpected {:?}", other),
+        }
+        // Multi-byte characters must not panic the GUID parser
+        let text = format!("{}é:0xaa", &GUID[..30]);
+        assert!(matches!(
+            parse_text_keys(&text),
+            Err(KeyLoadError::InvalidGuid { .. })
+        ));
+    }
+
+    #[test]
+    fn loads_env_keys_and_skips_unrelated_variables() {
+        let prefix = "RUEPARSE_TEST_KEYS";
+        // Nothing else in this crate touches these variables
+        unsafe {
+            std::env::set_var(prefix, "0xaa");
+            std::env::set_var(format!("{}_{}", prefix, GUID), "bb");
+            std::env::set_var(format!("{}_PATH", prefix), "/not/a/key");
+            #[cfg(unix)]
+            {
+                use std::os::unix::ffi::OsStrExt;
+                let value = std::ffi::OsStr::from_bytes(&[0xFF, 0xFE]);
+                std::env::set_var(format!("{}_INVALID", prefix), value);
+            }
+        }
+        let mut keys = parse_env_keys(prefix).unwrap();
+        keys.sort_by_key(|(guid, _)| *guid != FGuid::default());
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0].1.key_string, "0xaa");
+        assert_eq!(keys[1].0, FGuid::from_hex(GUID).unwrap());
+
+        unsafe { std::env::set_var(format!("{}_{}", prefix, GUID), "not hex") };
+        match parse_env_keys(prefix) {
+            Err(KeyLoadError::InvalidKey { location, .. }) => {
+                assert_eq!(location, format!("{}_{}", prefix, GUID))
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,3 @@
+mod keyfile;
+
+pub use keyfile::*;
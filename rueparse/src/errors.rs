@@ -1,5 +1,166 @@
+use std::error;
+use std::fmt;
 use std::io;
 
-pub enum Error {
-    ReadError(io::Error),
+use crate::readers::Reader;
+
+/// Parse failure with the byte offset, the file or chunk and the chain of structures being read,
+/// for example `FPackageSummary > ExportMap[12] > serial_size`.
+///
+/// Readers return `io::Error`, so this travels as its payload. `Error::from_io` gets it back
+/// out, and the `Display` of the `io::Error` is the full message.
+#[derive(Debug)]
+pub struct Error {
+    pub cause: io::Error,
+    pub offset: Option<u64>,
+    pub file: Option<String>,
+    /// Outermost structure first. Array indices are their own `[i]` segments.
+    pub path: Vec<String>,
+}
+
+impl Error {
+    pub fn new(kind: io::ErrorKind, message: impl Into<String>) -> Self {
+        Self::from_cause(io::Error::new(kind, message.into()))
+    }
+
+    fn from_cause(cause: io::Error) -> Self {
+        Self {
+            cause,
+            offset: None,
+            file: None,
+            path: Vec::new(),
+        }
+    }
+
+    /// Takes the `Error` out of an `io::Error` carrying one, or wraps a plain `io::Error`.
+    pub fn from_io(error: io::Error) -> Self {
+        if error.get_ref().is_some_and(|e| e.is::<Error>()) {
+            let kind = error.kind();
+            match error.into_inner().map(|e| e.downcast::<Error>()) {
+                Some(Ok(e)) => return *e,
+                Some(Err(e)) => return Self::from_cause(io::Error::new(kind, e)),
+                None => unreachable!(),
+            }
+        }
+        Self::from_cause(error)
+    }
+
+    pub fn kind(&self) -> io::ErrorKind {
+        self.cause.kind()
+    }
+
+    /// Sets the offset unless a more precise one was already recorded further in.
+    pub fn at(mut self, offset: u64) -> Self {
+        self.offset.get_or_insert(offset);
+        self
+    }
+
+    /// Uses the current position of `reader` as the offset, if there is none yet.
+    pub fn at_position(self, reader: &mut dyn Reader) -> Self {
+        match (self.offset, reader.position()) {
+            (None, Ok(position)) => self.at(position),
+            _ => self,
+        }
+    }
+
+    /// Adds the structure, field or `[i]` index the error happened in.
+    pub fn within(mut self, name: impl Into<String>) -> Self {
+        self.path.insert(0, name.into());
+        self
+    }
+
+    pub fn in_file(mut self, file: impl Into<String>) -> Self {
+        self.file.get_or_insert_with(|| file.into());
+        self
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}: ", file)?;
+        }
+        for (i, segment) in self.path.iter().enumerate() {
+            if i > 0 && !segment.starts_with('[') {
+                write!(f, " > ")?;
+            }
+            write!(f, "{}", segment)?;
+        }
+        if let Some(offset) = self.offset {
+            if !self.path.is_empty() {
+                write!(f, " ")?;
+            }
+            write!(f, "at offset {:#X}", offset)?;
+        }
+        if self.file.is_some() || !self.path.is_empty() || self.offset.is_some() {
+            write!(f, ": ")?;
+        }
+        write!(f, "{}", self.cause)
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.cause)
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(error: Error) -> Self {
+        io::Error::new(error.kind(), error)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Self::from_io(error)
+    }
+}
+
+/// Adds a structure or field name to an `io::Error`, keeping any context it already has.
+pub fn within(error: io::Error, name: impl Into<String>) -> io::Error {
+    Error::from_io(error).within(name).into()
+}
+
+/// Adds the index of the array element that failed to read.
+pub fn at_index(error: io::Error, index: usize) -> io::Error {
+    within(error, format!("[{}]", index))
+}
+
+/// Adds the file or chunk an error happened in.
+pub fn in_file(error: io::Error, file: impl Into<String>) -> io::Error {
+    Error::from_io(error).in_file(file).into()
+}
+
+/// Context for a field of a derived reader: its name and, if nothing further in recorded one,
+/// the position where reading stopped.
+pub fn field_error(error: io::Error, reader: &mut dyn Reader, field: &str) -> io::Error {
+    Error::from_io(error)
+        .at_position(reader)
+        .within(field)
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::readers::{FIoStoreTocHeader, FileReader, Reader};
+    use std::io::Cursor;
+
+    #[test]
+    fn errors_carry_path_and_offset() {
+        let mut reader = FileReader::new(Cursor::new(vec![0u8; 144]));
+        let e = FIoStoreTocHeader::from_reader(&mut reader).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "FIoStoreTocHeader > toc_magic at offset 0x0: Invalid TOC magic"
+        );
+
+        let mut reader = FileReader::new(Cursor::new(vec![0, 0, 7, 0, 0, 0]));
+        reader.skip(2).unwrap();
+        let e = super::in_file(reader.read_bool().unwrap_err(), "a.uasset");
+        assert_eq!(
+            e.to_string(),
+            "a.uasset: at offset 0x2: Invalid bool value 7"
+        );
+    }
 }
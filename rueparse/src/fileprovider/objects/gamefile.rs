@@ -33,6 +33,12 @@ impl GameFile {
             compression_method: CompressionMethod::Unknown,
         }
     }
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+    pub fn size(&self) -> i64 {
+        self.size
+    }
     pub fn extension(&mut self) -> String {
         self.path.rsplit('.').next().unwrap_or("").to_string()
     }
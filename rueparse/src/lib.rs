@@ -1,6 +1,10 @@
+// Lets `#[derive(UeRead)]` refer to `::rueparse` from inside this crate
+extern crate self as rueparse;
+
 pub mod assets;
 pub mod compression;
-mod errors;
+pub mod encryption;
+pub mod errors;
 pub mod fileprovider;
 pub mod mappings;
 pub mod models;
@@ -8,22 +12,45 @@ pub mod objects;
 pub mod readers;
 pub mod ue;
 pub mod versions;
+pub mod writers;
 
+use compression::CompressionRegistry;
+use encryption::KeyLoadError;
 use mappings::UsmapProvider;
 use oodle::Oodle;
 use std::io;
-use std::{collections::HashMap, fs, path::Path};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+};
 
 use fileprovider::objects::{DirectoryInfo, FileInfo, GameFile, OsGameFile};
 use hex::FromHexError;
 use models::{FAesKey, FGuid};
+use ue::io::IoStoreReader;
+use ue::io::objects::{EIoErrorCode, FIoStatus, FIoStoreEntry};
 pub use versions::*;
 
+/// Outcome of mounting containers.
+#[derive(Debug, Default)]
+pub struct MountSummary {
+    /// Number of files that became available.
+    pub mounted_files: usize,
+    /// Containers that failed for any reason other than a missing or wrong key, by path.
+    /// They are not kept locked, `mount` tries them again.
+    pub failed: Vec<(String, FIoStatus)>,
+}
+
 pub struct UEParse {
     pub mappings: Option<UsmapProvider>,
     pub oodle: Option<Oodle>,
+    pub compression: CompressionRegistry,
     pub keys: HashMap<FGuid, FAesKey>,
     pub working_directory: DirectoryInfo,
+    pub files: HashMap<String, FIoStoreEntry>,
+    pub mounted_containers: Vec<IoStoreReader>,
+    pub locked_containers: Vec<IoStoreReader>,
 }
 
 impl UEParse {
@@ -31,7 +58,11 @@ impl UEParse {
         Ok(UEParse {
             mappings: None,
             oodle: None,
+            compression: CompressionRegistry::default(),
             keys: HashMap::new(),
+            files: HashMap::new(),
+            mounted_containers: Vec::new(),
+            locked_containers: Vec::new(),
             working_directory: match DirectoryInfo::new(&path) {
                 Ok(d) => d,
                 Err(e) => return Err(e),
@@ -39,11 +70,15 @@ impl UEParse {
         })
     }
 
+    /// Loads the native Oodle library. A custom "Oodle" decompressor in `compression` is kept.
+    ///
+    /// There is no built-in Rust decoder, so without one of the two Oodle blocks fail to read.
     pub fn init_oodle(&mut self, path: &str) -> Result<(), oodle::Error> {
-        self.oodle.insert(match Oodle::load(path) {
+        let oodle = self.oodle.insert(match Oodle::load(path) {
             Ok(o) => o,
             Err(e) => return Err(e),
         });
+        self.compression.set_oodle(Some(oodle.clone()));
         Ok(())
     }
 
@@ -62,6 +97,127 @@ impl UEParse {
         self.keys.remove(guid)
     }
 
+    fn insert_keys(&mut self, keys: Vec<(FGuid, FAesKey)>) -> usize {
+        let count = keys.len();
+        for (guid, key) in keys {
+            self.keys.insert(guid, key);
+        }
+        count
+    }
+    pub fn load_keys_from_json(&mut self, path: &str) -> Result<usize, KeyLoadError> {
+        let keys = encryption::read_json_keys(path)?;
+        Ok(self.insert_keys(keys))
+    }
+    pub fn load_keys_from_text(&mut self, path: &str) -> Result<usize, KeyLoadError> {
+        let keys = encryption::read_text_keys(path)?;
+        Ok(self.insert_keys(keys))
+    }
+    pub fn load_keys_from_env(&mut self, prefix: &str) -> Result<usize, KeyLoadError> {
+        let keys = encryption::parse_env_keys(prefix)?;
+        Ok(self.insert_keys(keys))
+    }
+
+    /// Registers every container under the working directory and mounts those
+    /// that are unencrypted or whose key is already known.
+    pub fn mount(&mut self) -> MountSummary {
+        let known: HashSet<String> = self
+            .mounted_containers
+            .iter()
+            .chain(self.locked_containers.iter())
+            .map(|c| c.path.clone())
+            .collect();
+        let tocs = walkdir::WalkDir::new(&self.working_directory.path)
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|e| {
+                e.file_type().is_file()
+                    && e.path().extension().and_then(|e| e.to_str()) == Some("utoc")
+            })
+            .filter_map(|e| e.path().to_str().map(|p| p.to_string()))
+            .filter(|p| !known.contains(p))
+            .collect::<Vec<_>>();
+        let mut summary = MountSummary::default();
+        for toc in tocs {
+            match IoStoreReader::new(&toc) {
+                Ok(container) => self.locked_containers.push(container),
+                Err(e) => summary.failed.push((toc, e)),
+            }
+        }
+        self.mount_locked(None, &mut summary);
+        summary
+    }
+
+    /// Adds the keys and mounts every locked container they unlock.
+    pub fn submit_keys(&mut self, keys: Vec<(FGuid, FAesKey)>) -> MountSummary {
+        let guids: HashSet<FGuid> = keys.iter().map(|(guid, _)| *guid).collect();
+        self.insert_keys(keys);
+        let mut summary = MountSummary::default();
+        self.mount_locked(Some(&guids), &mut summary);
+        summary
+    }
+    pub fn submit_key(&mut self, guid: FGuid, key: FAesKey) -> MountSummary {
+        self.submit_keys(vec![(guid, key)])
+    }
+
+    // Containers whose index fails to decrypt stay locked so a correct key can be submitted later
+    fn mount_locked(&mut self, guids: Option<&HashSet<FGuid>>, summary: &mut MountSummary) {
+        for mut container in std::mem::take(&mut self.locked_containers) {
+            let guid = container.encryption_key_guid();
+            let key = if container.is_encrypted() {
+                self.keys.get(&guid).cloned()
+            } else {
+                None
+            };
+            let wanted = match guids {
+                Some(g) => g.contains(&guid),
+                None => true,
+            };
+            if !wanted || (container.is_encrypted() && key.is_none()) {
+                self.locked_containers.push(container);
+                continue;
+            }
+            match container.mount(key) {
+                Ok(files) => summary.mounted_files += self.merge_files(container, files),
+                Err(e) if e.error_code == EIoErrorCode::InvalidEncryptionKey => {
+                    self.locked_containers.push(container)
+                }
+                Err(e) => summary.failed.push((container.path.clone(), e)),
+            }
+        }
+    }
+
+    pub fn read_file(&self, path: &str) -> Result<Vec<u8>, FIoStatus> {
+        let entry = match self.files.get(&path.to_lowercase()) {
+            Some(e) => e,
+            None => {
+                return Err(FIoStatus::new(
+                    EIoErrorCode::NotFound,
+                    format!("{} not found", path),
+                ));
+            }
+        };
+        self.mounted_containers[entry.container_index]
+            .read_chunk(entry.toc_entry_index, &self.compression)
+    }
+
+    fn merge_files(&mut self, container: IoStoreReader, files: Vec<(String, u32)>) -> usize {
+        let container_index = self.mounted_containers.len();
+        let mut new_files = 0;
+        for (path, toc_entry_index) in files {
+            let size = container.toc.chunk_offset_lengths[toc_entry_index as usize].length();
+            let entry = FIoStoreEntry::new(path, size as i64, container_index, toc_entry_index);
+            if self
+                .files
+                .insert(entry.game_file.path().to_lowercase(), entry)
+                .is_none()
+            {
+                new_files += 1;
+            }
+        }
+        self.mounted_containers.push(container);
+        new_files
+    }
+
     fn iterate_files(
         &mut self,
         directory: &Path,
@@ -134,10 +290,200 @@ impl UEParse {
 #[cfg(test)]
 mod tests {
     use crate::{
+        UEParse,
+        compression::{CompressionMethod, DecompressionError, Decompressor, decompress},
         mappings::UsmapProvider,
+        models::{FAesKey, FGuid},
         readers::{FIoStoreTocHeader, FileReader},
+        ue::io::objects::EIoErrorCode,
     };
+    use aes::cipher::{BlockEncrypt, KeyInit, generic_array::GenericArray};
     use std::fs::File;
+    use std::path::Path;
+
+    const KEY: &str = "00112233445566778899aabbccddeeff00112233445566778899aabbccddeeff";
+
+    fn fstring(data: &mut Vec<u8>, value: &str) {
+        data.extend((value.len() as i32 + 1).to_le_bytes());
+        data.extend(value.as_bytes());
+        data.push(0);
+    }
+
+    fn u32s(data: &mut Vec<u8>, values: &[u32]) {
+        for value in values {
+            data.extend(value.to_le_bytes());
+        }
+    }
+
+    // Writes an encrypted, indexed container with Game/Content/a.uasset and b.uasset, sized 100
+    // and 200 bytes, pointing to the given TOC entries. Only the TOC is written.
+    fn write_encrypted_container(path: &Path, toc_entries: [u32; 2]) {
+        let mut index = Vec::new();
+        fstring(&mut index, "../../../Game/");
+        // Directories: name, first child, next sibling, first file
+        index.extend(2i32.to_le_bytes());
+        u32s(&mut index, &[u32::MAX, 1, u32::MAX, u32::MAX]);
+        u32s(&mut index, &[0, u32::MAX, u32::MAX, 0]);
+        // Files: name, next file, TOC entry
+        index.extend(2i32.to_le_bytes());
+        u32s(&mut index, &[1, 1, toc_entries[0]]);
+        u32s(&mut index, &[2, u32::MAX, toc_entries[1]]);
+        index.extend(3i32.to_le_bytes());
+        for name in ["Content", "a.uasset", "b.uasset"] {
+            fstring(&mut index, name);
+        }
+        index.resize(index.len().next_multiple_of(16), 0);
+        let cipher = aes::Aes256::new_from_slice(&hex::decode(KEY).unwrap()).unwrap();
+        for block in index.chunks_exact_mut(16) {
+            cipher.encrypt_block(GenericArray::from_mut_slice(block));
+        }
+
+        // Encrypted and indexed
+        write_toc(path, 2 | 8, &[100, 200], &[], &[], &index);
+    }
+
+    // Writes a TOC with back to back chunks of the given lengths, compression blocks of 0x10000
+    // bytes as (offset, compressed size, size, method), extra method names and a directory index
+    fn write_toc(
+        path: &Path,
+        flags: u8,
+        lengths: &[u64],
+        blocks: &[(u64, u32, u32, u8)],
+        methods: &[&str],
+        index: &[u8],
+    ) {
+        let mut toc = [0x2D, 0x3D, 0x3D, 0x2D].repeat(4);
+        toc.extend([8, 0, 0, 0]);
+        // Header size, entries, blocks, entry size, methods, name length, block size, index
+        // size and partitions
+        u32s(
+            &mut toc,
+            &[
+                144,
+                lengths.len() as u32,
+                blocks.len() as u32,
+                12,
+                methods.len() as u32,
+                32,
+                0x10000,
+                index.len() as u32,
+                1,
+            ],
+        );
+        toc.extend(5u64.to_le_bytes());
+        u32s(&mut toc, &[1, 2, 3, 4]);
+        toc.extend([flags, 0, 0, 0]);
+        toc.extend([0u8; 4 + 8 + 4 + 4 + 40]);
+        toc.extend(vec![0u8; 12 * lengths.len()]);
+        let mut offset = 0u64;
+        for length in lengths {
+            toc.extend(&offset.to_be_bytes()[3..]);
+            toc.extend(&length.to_be_bytes()[3..]);
+            offset += length;
+        }
+        for (offset, compressed_size, size, method) in blocks {
+            toc.extend(&offset.to_le_bytes()[..5]);
+            toc.extend(&compressed_size.to_le_bytes()[..3]);
+            toc.extend(&size.to_le_bytes()[..3]);
+            toc.push(*method);
+        }
+        for method in methods {
+            let mut name = [0u8; 32];
+            name[..method.len()].copy_from_slice(method.as_bytes());
+            toc.extend(name);
+        }
+        toc.extend(index);
+        std::fs::write(path, toc).unwrap();
+    }
+
+    #[test]
+    fn mounts_encrypted_containers_once_the_key_is_submitted() {
+        let dir = std::env::temp_dir().join(format!("rueparse-mount-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        write_encrypted_container(&dir.join("encrypted.utoc"), [0, 1]);
+        write_encrypted_container(&dir.join("dangling.utoc"), [0, 5]);
+        std::fs::write(dir.join("broken.utoc"), b"not a toc").unwrap();
+
+        let mut provider = UEParse::new(dir.to_str().unwrap()).unwrap();
+        let summary = provider.mount();
+        assert_eq!(summary.mounted_files, 0);
+        assert_eq!(summary.failed.len(), 1);
+        assert!(summary.failed[0].0.ends_with("broken.utoc"));
+        assert_eq!(summary.failed[0].1.error_code, EIoErrorCode::CorruptToc);
+        assert_eq!(provider.locked_containers.len(), 2);
+
+        let guid = FGuid::new(1, 2, 3, 4);
+        let summary = provider.submit_key(guid, FAesKey::from_str(&"11".repeat(32)).unwrap());
+        assert_eq!(summary.mounted_files, 0);
+        assert!(summary.failed.is_empty());
+        assert_eq!(provider.locked_containers.len(), 2);
+
+        // The dangling container decrypts, but is broken and no longer waits for a key
+        let summary = provider.submit_key(guid, FAesKey::from_str(KEY).unwrap());
+        assert_eq!(summary.mounted_files, 2);
+        assert_eq!(summary.failed.len(), 1);
+        assert!(summary.failed[0].0.ends_with("dangling.utoc"));
+        assert!(provider.locked_containers.is_empty());
+        assert_eq!(
+            provider.files["game/content/b.uasset"].game_file.size(),
+            200
+        );
+
+        let summary = provider.mount();
+        assert_eq!(summary.mounted_files, 0);
+        assert_eq!(summary.failed.len(), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // LZ4 under the name a game would give its own codec
+    struct GameLz;
+
+    impl Decompressor for GameLz {
+        fn decompress(&self, input: &[u8], output: &mut [u8]) -> Result<(), DecompressionError> {
+            decompress(CompressionMethod::LZ4, input, output, None)
+        }
+    }
+
+    #[test]
+    fn reads_blocks_with_registered_codecs() {
+        let dir = std::env::temp_dir().join(format!("rueparse-codec-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let data: Vec<u8> = (0..1000u32).map(|i| (i % 7) as u8).collect();
+        let compressed = lz4_flex::block::compress(&data);
+        std::fs::write(dir.join("game.ucas"), &compressed).unwrap();
+
+        let mut index = Vec::new();
+        fstring(&mut index, "../../../Game/");
+        index.extend(1i32.to_le_bytes());
+        u32s(&mut index, &[u32::MAX, u32::MAX, u32::MAX, 0]);
+        index.extend(1i32.to_le_bytes());
+        u32s(&mut index, &[0, u32::MAX, 0]);
+        index.extend(1i32.to_le_bytes());
+        fstring(&mut index, "data.bin");
+        // Indexed
+        write_toc(
+            &dir.join("game.utoc"),
+            8,
+            &[data.len() as u64],
+            &[(0, compressed.len() as u32, data.len() as u32, 1)],
+            &["GameLZ"],
+            &index,
+        );
+
+        // Unknown methods only fail once a block needs them
+        let mut provider = UEParse::new(dir.to_str().unwrap()).unwrap();
+        let summary = provider.mount();
+        assert_eq!(summary.mounted_files, 1);
+        assert!(summary.failed.is_empty());
+        let error = provider.read_file("Game/data.bin").unwrap_err();
+        assert!(error.error_message.contains("UnknownMethod(\"GameLZ\")"));
+
+        provider.compression.register("gamelz", Box::new(GameLz));
+        assert_eq!(provider.read_file("Game/data.bin").unwrap(), data);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 
     #[test]
     fn it_works() {
@@ -157,7 +503,7 @@ mod tests {
 
         let usmap_path = "/Volumes/DELIVERZ/mappings.usmap";
         let oo = oodle::Oodle::load(&"/Volumes/DELIVERZ/liboo2coremac64.2.9.13.dylib").unwrap();
-        let usmap = UsmapProvider::from_path(&usmap_path, &oo).unwrap();
+        let usmap = UsmapProvider::from_path(&usmap_path, Some(&oo)).unwrap();
         println!("{:?}", usmap);
     }
 }
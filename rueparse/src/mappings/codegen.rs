@@ -0,0 +1,463 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use super::{EPropertyType, PropertyInfo, PropertyType, Struct, TypeMappings};
+
+/// How a struct's super struct shows up in the generated code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuperStyle {
+    /// Copy the inherited fields in, base struct fields first.
+    Flatten,
+    /// Add a `base` field holding the super struct.
+    Compose,
+}
+
+/// Generates a standalone Rust module with one struct per mapped struct and one enum per mapped
+/// enum. Only `std` is used, so the output compiles without this crate.
+///
+/// Names are kept as they are in the mappings, only characters Rust does not allow are replaced.
+/// Sets become `Vec`, maps become `HashMap` when the key can be hashed and a `Vec` of pairs
+/// otherwise. Structs that contain themselves are boxed. Struct names that are referenced but
+/// not mapped get an empty struct.
+#[derive(Debug, Clone)]
+pub struct RustGenerator {
+    pub super_style: SuperStyle,
+}
+
+impl RustGenerator {
+    pub fn new(super_style: SuperStyle) -> Self {
+        Self { super_style }
+    }
+
+    pub fn generate(&self, mappings: &TypeMappings) -> String {
+        let mut generation = Generation::new(mappings, self.super_style);
+        generation.run()
+    }
+}
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "gen", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "static", "struct", "trait", "true", "type", "unsafe", "use", "where",
+    "while", "abstract", "become", "box", "do", "final", "macro", "override", "priv", "try",
+    "typeof", "unsized", "virtual", "yield",
+];
+
+/// Keywords that cannot be raw identifiers.
+const RESERVED: &[&str] = &["self", "Self", "super", "crate", "_"];
+
+/// Replaces what Rust does not allow in identifiers.
+fn identifier(name: &str) -> String {
+    let mut id: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if id.is_empty() || id.starts_with(|c: char| c.is_ascii_digit()) {
+        id.insert(0, '_');
+    }
+    if RESERVED.contains(&id.as_str()) {
+        id.push('_');
+    } else if KEYWORDS.contains(&id.as_str()) {
+        id.insert_str(0, "r#");
+    }
+    id
+}
+
+/// Appends `_2`, `_3`, ... until `name` is not in `used`. A suffixed keyword is no longer one.
+fn unique(name: String, used: &mut HashSet<String>) -> String {
+    let mut candidate = name.clone();
+    let mut n = 2;
+    while used.contains(&candidate) {
+        candidate = format!("{}_{}", name.trim_start_matches("r#"), n);
+        n += 1;
+    }
+    used.insert(candidate.clone());
+    candidate
+}
+
+struct Generation<'a> {
+    mappings: &'a TypeMappings,
+    super_style: SuperStyle,
+    /// Rust names of mapped structs, enums and the placeholders for unmapped structs.
+    struct_names: HashMap<String, String>,
+    enum_names: HashMap<String, String>,
+    placeholders: Vec<String>,
+    used: HashSet<String>,
+}
+
+impl<'a> Generation<'a> {
+    fn new(mappings: &'a TypeMappings, super_style: SuperStyle) -> Self {
+        let mut generation = Self {
+            mappings,
+            super_style,
+            struct_names: HashMap::new(),
+            enum_names: HashMap::new(),
+            placeholders: Vec::new(),
+            used: HashSet::new(),
+        };
+        for name in sorted(mappings.types.keys()) {
+            let id = unique(identifier(name), &mut generation.used);
+            generation.struct_names.insert(name.clone(), id);
+        }
+        for name in sorted(mappings.enums.keys()) {
+            let id = unique(identifier(name), &mut generation.used);
+            generation.enum_names.insert(name.clone(), id);
+        }
+        generation
+    }
+
+    fn run(&mut self) -> String {
+        let mut body = String::new();
+        for name in sorted(self.mappings.enums.keys()) {
+            self.write_enum(&mut body, name);
+        }
+        for name in sorted(self.mappings.types.keys()) {
+            self.write_struct(&mut body, &self.mappings.types[name]);
+        }
+
+        let mut out = String::new();
+        out.push_str("// Generated from type mappings.\n");
+        out.push_str("#![allow(non_camel_case_types, non_snake_case, dead_code)]\n\n");
+        for placeholder in &self.placeholders {
+            let _ = writeln!(out, "/// Referenced, but not in the mappings.");
+            let _ = writeln!(out, "#[derive(Debug, Clone, PartialEq)]");
+            let _ = writeln!(out, "pub struct {} {{}}\n", placeholder);
+        }
+        out.push_str(&body);
+        out
+    }
+
+    fn write_enum(&self, out: &mut String, name: &str) {
+        let values = &self.mappings.enums[name];
+        let mut entries: Vec<(&i64, &String)> = values.iter().collect();
+        entries.sort();
+
+        let _ = writeln!(out, "/// `{}`", name);
+        let _ = writeln!(out, "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]");
+        // rustc rejects `repr` on an enum without variants
+        if !entries.is_empty() {
+            let _ = writeln!(out, "#[repr(i64)]");
+        }
+        let _ = writeln!(out, "pub enum {} {{", self.enum_names[name]);
+        let mut used = HashSet::new();
+        for (value, entry) in entries {
+            // Values are unique keys already, only the names can clash
+            let short = entry.rsplit("::").next().unwrap_or(entry);
+            let variant = unique(identifier(short), &mut used);
+            let _ = writeln!(out, "    {} = {},", variant, value);
+        }
+        let _ = writeln!(out, "}}\n");
+    }
+
+    fn write_struct(&mut self, out: &mut String, s: &Struct) {
+        let mut fields: Vec<(String, String)> = Vec::new();
+        let mut used = HashSet::new();
+        match self.super_style {
+            SuperStyle::Flatten => {
+                let chain: Vec<&Struct> = s.chain().collect();
+                for ancestor in chain.iter().rev() {
+                    for property in ordered_properties(ancestor) {
+                        let field = unique(identifier(&property.name), &mut used);
+                        fields.push((field, self.property_type(s, property)));
+                    }
+                }
+            }
+            SuperStyle::Compose => {
+                if let Some(parent) = &s.super_struct {
+                    let field = unique("base".to_string(), &mut used);
+                    let ty = self.struct_type(s, &parent.name, true);
+                    fields.push((field, ty));
+                }
+                for property in ordered_properties(s) {
+                    let field = unique(identifier(&property.name), &mut used);
+                    fields.push((field, self.property_type(s, property)));
+                }
+            }
+        }
+
+        match s.super_struct.as_ref() {
+            Some(parent) => {
+                let _ = writeln!(out, "/// `{}`, inherits `{}`", s.name, parent.name);
+            }
+            None => {
+                let _ = writeln!(out, "/// `{}`", s.name);
+            }
+        }
+        let _ = writeln!(out, "#[derive(Debug, Clone, PartialEq)]");
+        let _ = writeln!(out, "pub struct {} {{", self.struct_names[&s.name]);
+        for (field, ty) in fields {
+            let _ = writeln!(out, "    pub {}: {},", field, ty);
+        }
+        let _ = writeln!(out, "}}\n");
+    }
+
+    fn property_type(&mut self, owner: &Struct, property: &PropertyInfo) -> String {
+        let ty = self.rust_type(owner, &property.mapping_type, true);
+        match property.array_size.unwrap_or(1) {
+            0 | 1 => ty,
+            n => format!("[{}; {}]", ty, n),
+        }
+    }
+
+    /// A struct used by `owner`, boxed when it is held inline and would contain `owner` again.
+    fn struct_type(&mut self, owner: &Struct, name: &str, inline: bool) -> String {
+        let id = match self.struct_names.get(name) {
+            Some(id) => id.clone(),
+            None => {
+                let id = unique(identifier(name), &mut self.used);
+                self.struct_names.insert(name.to_string(), id.clone());
+                self.placeholders.push(id.clone());
+                return id;
+            }
+        };
+        if inline && self.contains(name, &owner.name, &mut HashSet::new()) {
+            return format!("::std::boxed::Box<{}>", id);
+        }
+        id
+    }
+
+    /// Whether `name` holds `target` inline, directly or through other structs. Containers
+    /// allocate, so only plain, optional and static array fields count.
+    fn contains(&self, name: &str, target: &str, seen: &mut HashSet<String>) -> bool {
+        if name == target {
+            return true;
+        }
+        if !seen.insert(name.to_string()) {
+            return false;
+        }
+        let s = match self.mappings.types.get(name) {
+            Some(s) => s,
+            None => return false,
+        };
+        if self.super_style == SuperStyle::Compose
+            && s.super_struct
+                .as_ref()
+                .is_some_and(|parent| self.contains(&parent.name, target, seen))
+        {
+            return true;
+        }
+        let chain: Vec<&Struct> = match self.super_style {
+            SuperStyle::Flatten => s.chain().collect(),
+            SuperStyle::Compose => vec![s.as_ref()],
+        };
+        for ancestor in chain {
+            for property in ancestor.properties.values() {
+                let mut ty = &property.mapping_type;
+                while ty.f_type == "OptionalProperty" {
+                    match &ty.inner_type {
+                        Some(inner) => ty = inner,
+                        None => break,
+                    }
+                }
+                if ty
+                    .struct_type
+                    .as_ref()
+                    .is_some_and(|inner| self.contains(inner, target, seen))
+                {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    fn rust_type(&mut self, owner: &Struct, property_type: &PropertyType, inline: bool) -> String {
+        let inner = |generation: &mut Self, ty: &Option<Box<PropertyType>>, inline| match ty {
+            Some(t) => generation.rust_type(owner, t, inline),
+            None => "()".to_string(),
+        };
+        match EPropertyType::from_name(&property_type.f_type) {
+            EPropertyType::BoolProperty => "bool".to_string(),
+            EPropertyType::ByteProperty => "u8".to_string(),
+            EPropertyType::Int8Property => "i8".to_string(),
+            EPropertyType::Int16Property => "i16".to_string(),
+            EPropertyType::IntProperty => "i32".to_string(),
+            EPropertyType::Int64Property => "i64".to_string(),
+            EPropertyType::UInt16Property => "u16".to_string(),
+            EPropertyType::UInt32Property => "u32".to_string(),
+            EPropertyType::UInt64Property => "u64".to_string(),
+            EPropertyType::FloatProperty => "f32".to_string(),
+            EPropertyType::DoubleProperty => "f64".to_string(),
+            // Names, text and object references as their string form
+            EPropertyType::NameProperty
+            | EPropertyType::StrProperty
+            | EPropertyType::TextProperty
+            | EPropertyType::Utf8StrProperty
+            | EPropertyType::AnsiStrProperty
+            | EPropertyType::ObjectProperty
+            | EPropertyType::WeakObjectProperty
+            | EPropertyType::LazyObjectProperty
+            | EPropertyType::AssetObjectProperty
+            | EPropertyType::SoftObjectProperty
+            | EPropertyType::InterfaceProperty
+            | EPropertyType::FieldPathProperty
+            | EPropertyType::DelegateProperty
+            | EPropertyType::MulticastDelegateProperty => "::std::string::String".to_string(),
+            EPropertyType::ArrayProperty | EPropertyType::SetProperty => {
+                format!(
+                    "::std::vec::Vec<{}>",
+                    inner(self, &property_type.inner_type, false)
+                )
+            }
+            EPropertyType::OptionalProperty => {
+                format!(
+                    "::std::option::Option<{}>",
+                    inner(self, &property_type.inner_type, inline)
+                )
+            }
+            EPropertyType::MapProperty => {
+                let key = inner(self, &property_type.inner_type, false);
+                let value = inner(self, &property_type.value_type, false);
+                if self.hashable(&property_type.inner_type) {
+                    format!("::std::collections::HashMap<{}, {}>", key, value)
+                } else {
+                    format!("::std::vec::Vec<({}, {})>", key, value)
+                }
+            }
+            EPropertyType::StructProperty => match &property_type.struct_type {
+                Some(name) => self.struct_type(owner, name, inline),
+                None => "()".to_string(),
+            },
+            EPropertyType::EnumProperty => {
+                match property_type
+                    .enum_name
+                    .as_ref()
+                    .and_then(|n| self.enum_names.get(n))
+                {
+                    Some(id) => id.clone(),
+                    // Without the enum, fall back to its underlying integer
+                    None => inner(self, &property_type.inner_type, inline),
+                }
+            }
+            EPropertyType::Unknown => "::std::vec::Vec<u8>".to_string(),
+        }
+    }
+
+    fn hashable(&self, key: &Option<Box<PropertyType>>) -> bool {
+        let key = match key {
+            Some(k) => k,
+            None => return false,
+        };
+        match EPropertyType::from_name(&key.f_type) {
+            EPropertyType::FloatProperty
+            | EPropertyType::DoubleProperty
+            | EPropertyType::StructProperty
+            | EPropertyType::MapProperty
+            | EPropertyType::Unknown => false,
+            EPropertyType::ArrayProperty
+            | EPropertyType::SetProperty
+            | EPropertyType::OptionalProperty => self.hashable(&key.inner_type),
+            EPropertyType::EnumProperty => match &key.enum_name {
+                Some(n) if self.enum_names.contains_key(n) => true,
+                _ => self.hashable(&key.inner_type),
+            },
+            _ => true,
+        }
+    }
+}
+
+fn sorted<'b>(keys: impl Iterator<Item = &'b String>) -> Vec<&'b String> {
+    let mut keys: Vec<&String> = keys.collect();
+    keys.sort();
+    keys
+}
+
+/// Own properties in index order, static arrays once.
+fn ordered_properties(s: &Struct) -> Vec<&PropertyInfo> {
+    let mut properties: Vec<(&i32, &PropertyInfo)> =
+        s.properties.iter().filter(|(_, p)| p.index == 0).collect();
+    properties.sort_by_key(|(i, _)| **i);
+    properties.into_iter().map(|(_, p)| p).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RustGenerator, SuperStyle};
+    use crate::mappings::parse_json_mappings;
+    use std::process::Command;
+
+    const DUMP: &str = r#"{
+        "classes": [
+            { "name": "Actor", "properties": [
+                { "name": "type", "type": "Name" },
+                { "name": "Bounds", "type": { "type": "Struct", "struct": "Box" } }
+            ] },
+            { "name": "BP_Pawn_C", "super": "Actor", "properties": [
+                { "name": "Slots", "arraySize": 40, "type": "Int" },
+                { "name": "Color", "type": { "type": "Enum", "enum": "EColor" } },
+                { "name": "Weights", "type": { "type": "Map", "inner": "Float", "value": "Int" } },
+                { "name": "ByColor", "type": { "type": "Map", "inner": { "type": "Enum", "enum": "EColor" }, "value": "Str" } },
+                { "name": "Next", "type": { "type": "Optional", "inner": { "type": "Struct", "struct": "BP_Pawn_C" } } },
+                { "name": "Children", "type": { "type": "Array", "inner": { "type": "Struct", "struct": "BP_Pawn_C" } } },
+                { "name": "Transform", "type": { "type": "Struct", "struct": "Transform" } },
+                { "name": "type", "type": "Bool" }
+            ] }
+        ],
+        "structs": [{ "name": "Box", "properties": [
+            { "name": "Min", "type": "Double" },
+            { "name": "Mode", "type": { "type": "Enum", "enum": "EEmpty" } }
+        ] }],
+        "enums": [
+            { "name": "EColor", "values": { "EColor::Red": 0, "EColor::Blue": 2, "EColor::MAX": 3 } },
+            { "name": "EEmpty", "values": [] }
+        ]
+    }"#;
+
+    /// Compiles `source` as a library, `rustc` has to be on the path.
+    fn check_compiles(source: &str, name: &str) {
+        let dir =
+            std::env::temp_dir().join(format!("rueparse-codegen-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("generated.rs");
+        std::fs::write(&path, source).unwrap();
+        let output = Command::new("rustc")
+            .args(["--crate-type", "lib", "--edition", "2021", "--out-dir"])
+            .arg(&dir)
+            .arg(&path)
+            .output()
+            .expect("rustc is needed to check the generated code");
+        let _ = std::fs::remove_dir_all(&dir);
+        assert!(
+            output.status.success(),
+            "{}\n{}",
+            String::from_utf8_lossy(&output.stderr),
+            source
+        );
+    }
+
+    #[test]
+    fn generates_compiling_structs_and_enums() {
+        let mappings = parse_json_mappings(DUMP).unwrap();
+
+        let flat = RustGenerator::new(SuperStyle::Flatten).generate(&mappings);
+        assert!(flat.contains("pub enum EColor {\n    Red = 0,\n    Blue = 2,\n    MAX = 3,\n}"));
+        assert!(flat.contains("pub struct Transform {}"));
+        assert!(
+            flat.contains(
+                "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]\npub enum EEmpty {\n}"
+            )
+        );
+        assert!(flat.contains("pub r#type: ::std::string::String,"));
+        assert!(flat.contains("pub type_2: bool,"));
+        assert!(flat.contains("pub Slots: [i32; 40],"));
+        assert!(flat.contains("pub Weights: ::std::vec::Vec<(f32, i32)>,"));
+        assert!(
+            flat.contains(
+                "pub ByColor: ::std::collections::HashMap<EColor, ::std::string::String>,"
+            )
+        );
+        assert!(flat.contains("pub Next: ::std::option::Option<::std::boxed::Box<BP_Pawn_C>>,"));
+        assert!(flat.contains("pub Children: ::std::vec::Vec<BP_Pawn_C>,"));
+        check_compiles(&flat, "flat");
+
+        let composed = RustGenerator::new(SuperStyle::Compose).generate(&mappings);
+        assert!(composed.contains("pub struct BP_Pawn_C {\n    pub base: Actor,"));
+        check_compiles(&composed, "composed");
+    }
+}
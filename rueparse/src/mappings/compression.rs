@@ -1,3 +1,5 @@
+use crate::compression::CompressionMethod;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EUsmapCompressionMethod {
     None,
@@ -19,3 +21,15 @@ impl From<u8> for EUsmapCompressionMethod {
         };
     }
 }
+
+impl From<EUsmapCompressionMethod> for CompressionMethod {
+    fn from(method: EUsmapCompressionMethod) -> Self {
+        match method {
+            EUsmapCompressionMethod::None => CompressionMethod::None,
+            EUsmapCompressionMethod::Oodle => CompressionMethod::Oodle,
+            EUsmapCompressionMethod::Brotli => CompressionMethod::Brotli,
+            EUsmapCompressionMethod::ZStandart => CompressionMethod::Zstd,
+            EUsmapCompressionMethod::Unknown => CompressionMethod::Unknown,
+        }
+    }
+}
@@ -0,0 +1,361 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use serde_json::{Value, json};
+
+use super::{PropertyInfo, Struct, TypeMappings};
+
+/// Old and new value of something that changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change<T> {
+    pub old: T,
+    pub new: T,
+}
+
+impl<T: PartialEq> Change<T> {
+    fn between(old: T, new: T) -> Option<Self> {
+        if old == new {
+            None
+        } else {
+            Some(Self { old, new })
+        }
+    }
+}
+
+/// Property changes, matched by name. Indices are the struct's own, without its supers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropertyDiff {
+    pub name: String,
+    pub index: Option<Change<i32>>,
+    pub array_size: Option<Change<u8>>,
+    /// Whole type trees as written by `PropertyType`'s `Display`, so nested changes show up.
+    pub property_type: Option<Change<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructDiff {
+    pub name: String,
+    pub super_type: Option<Change<String>>,
+    pub property_count: Option<Change<i32>>,
+    pub added_properties: Vec<String>,
+    pub removed_properties: Vec<String>,
+    pub changed_properties: Vec<PropertyDiff>,
+}
+
+/// Enum entries, matched by name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnumDiff {
+    pub name: String,
+    pub added_values: Vec<(String, i64)>,
+    pub removed_values: Vec<(String, i64)>,
+    pub changed_values: Vec<(String, Change<i64>)>,
+}
+
+/// Differences from one `TypeMappings` to a newer one. Every list is sorted by name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MappingsDiff {
+    pub added_structs: Vec<String>,
+    pub removed_structs: Vec<String>,
+    pub changed_structs: Vec<StructDiff>,
+    pub added_enums: Vec<String>,
+    pub removed_enums: Vec<String>,
+    pub changed_enums: Vec<EnumDiff>,
+}
+
+impl TypeMappings {
+    pub fn diff(&self, newer: &TypeMappings) -> MappingsDiff {
+        let mut diff = MappingsDiff::default();
+        let (added, removed, common) = split_keys(&self.types, &newer.types);
+        diff.added_structs = added;
+        diff.removed_structs = removed;
+        for name in common {
+            if let Some(d) = diff_struct(&self.types[&name], &newer.types[&name]) {
+                diff.changed_structs.push(d);
+            }
+        }
+
+        let (added, removed, common) = split_keys(&self.enums, &newer.enums);
+        diff.added_enums = added;
+        diff.removed_enums = removed;
+        for name in common {
+            if let Some(d) = diff_enum(&name, &self.enums[&name], &newer.enums[&name]) {
+                diff.changed_enums.push(d);
+            }
+        }
+        diff
+    }
+}
+
+/// Added, removed and common keys, each sorted.
+fn split_keys<V>(
+    old: &HashMap<String, V>,
+    new: &HashMap<String, V>,
+) -> (Vec<String>, Vec<String>, Vec<String>) {
+    let old_keys: BTreeSet<&String> = old.keys().collect();
+    let new_keys: BTreeSet<&String> = new.keys().collect();
+    let owned = |keys: Vec<&&String>| keys.into_iter().map(|k| k.to_string()).collect();
+    (
+        owned(new_keys.difference(&old_keys).collect()),
+        owned(old_keys.difference(&new_keys).collect()),
+        owned(old_keys.intersection(&new_keys).collect()),
+    )
+}
+
+/// Static arrays are stored once per element, the first one stands for the property.
+fn own_properties(s: &Struct) -> BTreeMap<&str, (i32, &PropertyInfo)> {
+    s.properties
+        .iter()
+        .filter(|(_, p)| p.index == 0)
+        .map(|(i, p)| (p.name.as_str(), (*i, p)))
+        .collect()
+}
+
+fn diff_struct(old: &Struct, new: &Struct) -> Option<StructDiff> {
+    let super_name = |s: &Struct| s.super_type.clone().unwrap_or_default();
+    let mut diff = StructDiff {
+        name: new.name.clone(),
+        super_type: Change::between(super_name(old), super_name(new)),
+        property_count: Change::between(old.property_count, new.property_count),
+        added_properties: Vec::new(),
+        removed_properties: Vec::new(),
+        changed_properties: Vec::new(),
+    };
+
+    let old_properties = own_properties(old);
+    let new_properties = own_properties(new);
+    for (name, (index, property)) in &new_properties {
+        let (old_index, old_property) = match old_properties.get(name) {
+            Some(p) => p,
+            None => {
+                diff.added_properties.push(name.to_string());
+                continue;
+            }
+        };
+        let property_diff = PropertyDiff {
+            name: name.to_string(),
+            index: Change::between(*old_index, *index),
+            array_size: Change::between(
+                old_property.array_size.unwrap_or(1),
+                property.array_size.unwrap_or(1),
+            ),
+            property_type: Change::between(
+                old_property.mapping_type.to_string(),
+                property.mapping_type.to_string(),
+            ),
+        };
+        if property_diff.index.is_some()
+            || property_diff.array_size.is_some()
+            || property_diff.property_type.is_some()
+        {
+            diff.changed_properties.push(property_diff);
+        }
+    }
+    for name in old_properties.keys() {
+        if !new_properties.contains_key(name) {
+            diff.removed_properties.push(name.to_string());
+        }
+    }
+
+    if diff.super_type.is_none()
+        && diff.property_count.is_none()
+        && diff.added_properties.is_empty()
+        && diff.removed_properties.is_empty()
+        && diff.changed_properties.is_empty()
+    {
+        return None;
+    }
+    Some(diff)
+}
+
+fn diff_enum(
+    name: &str,
+    old: &HashMap<i64, String>,
+    new: &HashMap<i64, String>,
+) -> Option<EnumDiff> {
+    let by_name = |values: &HashMap<i64, String>| -> BTreeMap<String, i64> {
+        values.iter().map(|(v, n)| (n.clone(), *v)).collect()
+    };
+    let old_values = by_name(old);
+    let new_values = by_name(new);
+    let mut diff = EnumDiff {
+        name: name.to_string(),
+        added_values: Vec::new(),
+        removed_values: Vec::new(),
+        changed_values: Vec::new(),
+    };
+    for (entry, value) in &new_values {
+        match old_values.get(entry) {
+            Some(old_value) => {
+                if let Some(change) = Change::between(*old_value, *value) {
+                    diff.changed_values.push((entry.clone(), change));
+                }
+            }
+            None => diff.added_values.push((entry.clone(), *value)),
+        }
+    }
+    for (entry, value) in &old_values {
+        if !new_values.contains_key(entry) {
+            diff.removed_values.push((entry.clone(), *value));
+        }
+    }
+
+    if diff.added_values.is_empty()
+        && diff.removed_values.is_empty()
+        && diff.changed_values.is_empty()
+    {
+        return None;
+    }
+    Some(diff)
+}
+
+fn change_json<T: Into<Value> + Clone>(change: &Option<Change<T>>) -> Value {
+    match change {
+        Some(c) => {
+            let (old, new): (Value, Value) = (c.old.clone().into(), c.new.clone().into());
+            json!({ "old": old, "new": new })
+        }
+        None => Value::Null,
+    }
+}
+
+impl MappingsDiff {
+    pub fn is_empty(&self) -> bool {
+        *self == MappingsDiff::default()
+    }
+
+    /// Unchanged fields of changed properties are `null`.
+    pub fn to_json(&self) -> Value {
+        let structs: Vec<Value> = self
+            .changed_structs
+            .iter()
+            .map(|s| {
+                let properties: Vec<Value> = s
+                    .changed_properties
+                    .iter()
+                    .map(|p| {
+                        json!({
+                            "name": p.name,
+                            "index": change_json(&p.index),
+                            "arraySize": change_json(&p.array_size),
+                            "type": change_json(&p.property_type),
+                        })
+                    })
+                    .collect();
+                json!({
+                    "name": s.name,
+                    "super": change_json(&s.super_type),
+                    "propertyCount": change_json(&s.property_count),
+                    "addedProperties": s.added_properties,
+                    "removedProperties": s.removed_properties,
+                    "changedProperties": properties,
+                })
+            })
+            .collect();
+        let enums: Vec<Value> = self
+            .changed_enums
+            .iter()
+            .map(|e| {
+                let changed: Vec<Value> = e
+                    .changed_values
+                    .iter()
+                    .map(|(n, c)| json!({ "name": n, "old": c.old, "new": c.new }))
+                    .collect();
+                json!({
+                    "name": e.name,
+                    "addedValues": e.added_values,
+                    "removedValues": e.removed_values,
+                    "changedValues": changed,
+                })
+            })
+            .collect();
+        json!({
+            "addedStructs": self.added_structs,
+            "removedStructs": self.removed_structs,
+            "changedStructs": structs,
+            "addedEnums": self.added_enums,
+            "removedEnums": self.removed_enums,
+            "changedEnums": enums,
+        })
+    }
+
+    pub fn to_json_string(&self) -> String {
+        match serde_json::to_string_pretty(&self.to_json()) {
+            Ok(s) => s,
+            // A `Value` built from strings and numbers always serializes
+            Err(_) => unreachable!(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mappings::{Change, parse_json_mappings};
+
+    const OLD: &str = r#"{
+        "classes": [
+            { "name": "Pawn", "super": "Actor", "properties": [
+                { "name": "Health", "type": "Float" },
+                { "name": "Tags", "type": { "type": "Array", "inner": "Name" } },
+                { "name": "Ammo", "type": "Int" }
+            ] },
+            { "name": "Actor" }, { "name": "Gone" }
+        ],
+        "enums": [{ "name": "EColor", "values": ["Red", "Green", "Blue"] }, { "name": "EOld", "values": [] }]
+    }"#;
+
+    const NEW: &str = r#"{
+        "classes": [
+            { "name": "Pawn", "super": "Character", "properties": [
+                { "name": "Health", "type": "Float" },
+                { "name": "Shield", "type": "Float" },
+                { "name": "Tags", "type": { "type": "Array", "inner": "Str" } }
+            ] },
+            { "name": "Actor" }, { "name": "Character", "super": "Actor" }
+        ],
+        "enums": [{ "name": "EColor", "values": { "Red": 0, "Blue": 1, "Purple": 2 } }]
+    }"#;
+
+    #[test]
+    fn reports_structs_properties_and_enums() {
+        let old = parse_json_mappings(OLD).unwrap();
+        let new = parse_json_mappings(NEW).unwrap();
+        assert!(old.diff(&old).is_empty());
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.added_structs, ["Character"]);
+        assert_eq!(diff.removed_structs, ["Gone"]);
+        assert_eq!(diff.removed_enums, ["EOld"]);
+        assert_eq!(diff.changed_structs.len(), 1);
+
+        let pawn = &diff.changed_structs[0];
+        assert_eq!(pawn.super_type.as_ref().unwrap().new, "Character");
+        assert_eq!(pawn.property_count, None);
+        assert_eq!(pawn.added_properties, ["Shield"]);
+        assert_eq!(pawn.removed_properties, ["Ammo"]);
+        let tags = &pawn.changed_properties[0];
+        assert_eq!(tags.name, "Tags");
+        assert_eq!(tags.index, Some(Change { old: 1, new: 2 }));
+        assert_eq!(
+            tags.property_type,
+            Some(Change {
+                old: "ArrayProperty<NameProperty>".to_string(),
+                new: "ArrayProperty<StrProperty>".to_string(),
+            })
+        );
+
+        let color = &diff.changed_enums[0];
+        assert_eq!(color.added_values, [("Purple".to_string(), 2)]);
+        assert_eq!(color.removed_values, [("Green".to_string(), 1)]);
+        assert_eq!(
+            color.changed_values,
+            [("Blue".to_string(), Change { old: 2, new: 1 })]
+        );
+
+        let json = diff.to_json();
+        assert_eq!(
+            json["changedStructs"][0]["changedProperties"][0]["index"]["new"],
+            2
+        );
+        assert_eq!(json["changedEnums"][0]["removedValues"][0][0], "Green");
+        assert!(diff.to_json_string().contains("\"addedStructs\""));
+    }
+}
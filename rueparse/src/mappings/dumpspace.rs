@@ -0,0 +1,472 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde_json::Value;
+
+use super::{JsonMappingsError, PropertyInfo, PropertyType, Struct, TypeMappings};
+
+/// Classes Dumper-7 fills with the engine's own layout instead of reflected properties.
+const PREDEFINED_CLASSES: [&str; 7] = [
+    "UObject",
+    "UField",
+    "UStruct",
+    "UClass",
+    "UFunction",
+    "UEnum",
+    "UProperty",
+];
+
+/// Drops the `U`, `A` or `F` Dumper-7 puts in front of class and struct names.
+fn strip_prefix(name: &str) -> &str {
+    match name.strip_prefix(['U', 'A', 'F']) {
+        Some(s) if !s.is_empty() => s,
+        _ => name,
+    }
+}
+
+fn invalid(location: &str) -> JsonMappingsError {
+    JsonMappingsError::InvalidField(location.to_string())
+}
+
+/// Every name, location and description in `data`, which holds objects of names to descriptions.
+type Entries<'a> = Vec<(&'a str, String, &'a Value)>;
+
+fn entries<'a>(root: &'a Value, file: &str) -> Result<Entries<'a>, JsonMappingsError> {
+    let data = match root.get("data") {
+        Some(Value::Array(a)) => a,
+        Some(_) => return Err(invalid(&format!("{}.data", file))),
+        None => return Err(JsonMappingsError::MissingField(format!("{}.data", file))),
+    };
+    let mut entries = Vec::new();
+    for (i, item) in data.iter().enumerate() {
+        match item {
+            Value::Object(o) => {
+                for (name, value) in o {
+                    let location = format!("{}.data[{}].{}", file, i, name);
+                    entries.push((name.as_str(), location, value));
+                }
+            }
+            _ => return Err(invalid(&format!("{}.data[{}]", file, i))),
+        }
+    }
+    Ok(entries)
+}
+
+fn primitive(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "bool" => "BoolProperty",
+        "int8" => "Int8Property",
+        "uint8" => "ByteProperty",
+        "int16" => "Int16Property",
+        "uint16" => "UInt16Property",
+        "int32" => "IntProperty",
+        "uint32" => "UInt32Property",
+        "int64" => "Int64Property",
+        "uint64" => "UInt64Property",
+        "float" => "FloatProperty",
+        "double" => "DoubleProperty",
+        "FName" => "NameProperty",
+        "FString" => "StrProperty",
+        "FText" => "TextProperty",
+        "FUtf8String" => "Utf8StrProperty",
+        "FAnsiString" => "AnsiStrProperty",
+        _ => return None,
+    })
+}
+
+fn simple(f_type: &str) -> PropertyType {
+    PropertyType::new(f_type.to_string(), None, None, None, None, None, None)
+}
+
+/// Types are `[name, kind, pointer, [subtypes]]`, where the kind is `C` for classes, `S` for
+/// structs, `E` for enums and `D` for everything else.
+fn parse_type(
+    value: &Value,
+    location: &str,
+    underlying: &HashMap<String, &'static str>,
+) -> Result<PropertyType, JsonMappingsError> {
+    let (name, kind, subtypes) = match value.as_array().map(|a| a.as_slice()) {
+        Some(
+            [
+                Value::String(name),
+                Value::String(kind),
+                _,
+                Value::Array(subtypes),
+                ..,
+            ],
+        ) => (name.as_str(), kind.as_str(), subtypes.as_slice()),
+        _ => return Err(invalid(location)),
+    };
+    let subtype = |i: usize| -> Result<Option<Box<PropertyType>>, JsonMappingsError> {
+        let location = format!("{}[3][{}]", location, i);
+        match subtypes.get(i) {
+            Some(v) => Ok(Some(Box::new(parse_type(v, &location, underlying)?))),
+            None => Err(JsonMappingsError::MissingField(location)),
+        }
+    };
+    let container = |f_type: &str, inner, value| {
+        PropertyType::new(f_type.to_string(), None, inner, value, None, None, None)
+    };
+    let enum_type = |name: &str, inner: &'static str| {
+        let mut t = simple("EnumProperty");
+        t.inner_type = Some(Box::new(simple(inner)));
+        t.enum_name = Some(name.to_string());
+        t
+    };
+
+    if let Some(f_type) = primitive(name) {
+        return Ok(simple(f_type));
+    }
+    Ok(match name {
+        "TArray" => container("ArrayProperty", subtype(0)?, None),
+        "TSet" => container("SetProperty", subtype(0)?, None),
+        "TOptional" => container("OptionalProperty", subtype(0)?, None),
+        "TMap" => container("MapProperty", subtype(0)?, subtype(1)?),
+        "TEnumAsByte" => match subtypes
+            .first()
+            .and_then(|s| s.get(0))
+            .and_then(|s| s.as_str())
+        {
+            Some(e) => enum_type(e, "ByteProperty"),
+            None => return Err(invalid(&format!("{}[3][0]", location))),
+        },
+        "TSubclassOf" => simple("ObjectProperty"),
+        "TWeakObjectPtr" => simple("WeakObjectProperty"),
+        "TLazyObjectPtr" => simple("LazyObjectProperty"),
+        "TSoftObjectPtr" | "TSoftClassPtr" => simple("SoftObjectProperty"),
+        "TScriptInterface" => simple("InterfaceProperty"),
+        "TFieldPath" => simple("FieldPathProperty"),
+        "TDelegate" | "FScriptDelegate" => simple("DelegateProperty"),
+        "TMulticastInlineDelegate"
+        | "TMulticastSparseDelegate"
+        | "FMulticastInlineDelegate"
+        | "FMulticastSparseDelegate" => simple("MulticastDelegateProperty"),
+        _ => match kind {
+            "C" => simple("ObjectProperty"),
+            "S" => PropertyType::new(
+                "StructProperty".to_string(),
+                Some(strip_prefix(name).to_string()),
+                None,
+                None,
+                None,
+                None,
+                None,
+            ),
+            "E" => enum_type(
+                name,
+                underlying.get(name).copied().unwrap_or("ByteProperty"),
+            ),
+            _ => return Err(invalid(location)),
+        },
+    })
+}
+
+/// Members are `{ name: [type, offset, size, arrayDim, bitOffset?] }`, next to `__`-prefixed
+/// entries such as `__InheritInfo`, the list of supers starting with the closest one.
+fn parse_struct(
+    name: &str,
+    value: &Value,
+    location: &str,
+    underlying: &HashMap<String, &'static str>,
+) -> Result<Struct, JsonMappingsError> {
+    let list = match value.as_array() {
+        Some(a) => a,
+        None => return Err(invalid(location)),
+    };
+    let predefined = PREDEFINED_CLASSES.contains(&name);
+    let mut super_type = String::new();
+    // (offset, bit offset, name, type, array size)
+    let mut members = Vec::new();
+    for (i, item) in list.iter().enumerate() {
+        let (member, data) = match item.as_object().filter(|o| o.len() == 1) {
+            Some(o) => o.iter().next().unwrap(),
+            None => return Err(invalid(&format!("{}[{}]", location, i))),
+        };
+        let location = format!("{}.{}", location, member);
+        if member == "__InheritInfo" {
+            super_type = match data.as_array().map(|a| a.first()) {
+                Some(Some(Value::String(s))) => strip_prefix(s).to_string(),
+                Some(None) => String::new(),
+                _ => return Err(invalid(&location)),
+            };
+            continue;
+        }
+        if member.starts_with("__") || predefined {
+            continue;
+        }
+        let fields = match data.as_array() {
+            Some(a) if a.len() >= 4 => a,
+            _ => return Err(invalid(&location)),
+        };
+        let property_type = parse_type(&fields[0], &format!("{}[0]", location), underlying)?;
+        let offset = match fields[1].as_u64() {
+            Some(o) => o,
+            None => return Err(invalid(&format!("{}[1]", location))),
+        };
+        let array_size = match fields[3].as_u64().map(u8::try_from) {
+            Some(Ok(s)) if s > 0 => s,
+            _ => return Err(invalid(&format!("{}[3]", location))),
+        };
+        let bit_offset = fields.get(4).and_then(|b| b.as_u64()).unwrap_or(0);
+        members.push((
+            offset,
+            bit_offset,
+            member.clone(),
+            property_type,
+            array_size,
+        ));
+    }
+
+    // Properties are numbered in memory order, static arrays taking one index per element
+    members.sort_by_key(|m| (m.0, m.1));
+    let mut properties = HashMap::new();
+    let mut next_index = 0;
+    for (_, _, member, property_type, array_size) in members {
+        let info = PropertyInfo::new(next_index, member, property_type, Some(array_size));
+        for element in 0..array_size as i32 {
+            let mut clone = info.clone();
+            clone.index = element;
+            properties.insert(next_index + element, clone);
+        }
+        next_index += array_size as i32;
+    }
+    Ok(Struct::new_with_super(
+        strip_prefix(name).to_string(),
+        Some(super_type),
+        properties,
+        next_index,
+    ))
+}
+
+/// Enums are `{ name: [[{ entry: value }, ...], underlyingType] }`.
+fn parse_enum(
+    value: &Value,
+    location: &str,
+) -> Result<(HashMap<i64, String>, &'static str), JsonMappingsError> {
+    let (list, underlying) = match value.as_array().map(|a| a.as_slice()) {
+        Some([Value::Array(list), Value::String(underlying), ..]) => (list, underlying),
+        _ => return Err(invalid(location)),
+    };
+    let underlying = match primitive(underlying) {
+        Some(t) => t,
+        None => return Err(invalid(&format!("{}[1]", location))),
+    };
+    let mut values = HashMap::new();
+    for (i, item) in list.iter().enumerate() {
+        let (entry, v) = match item.as_object().filter(|o| o.len() == 1) {
+            Some(o) => o.iter().next().unwrap(),
+            None => return Err(invalid(&format!("{}[0][{}]", location, i))),
+        };
+        let v = match v.as_i64() {
+            Some(v) => v,
+            None => return Err(invalid(&format!("{}[0][{}].{}", location, i, entry))),
+        };
+        if values.insert(v, entry.clone()).is_some() {
+            return Err(JsonMappingsError::DuplicateValue(format!(
+                "{}[0][{}].{}",
+                location, i, entry
+            )));
+        }
+    }
+    Ok((values, underlying))
+}
+
+fn parse_root(json: &str) -> Result<Value, JsonMappingsError> {
+    match serde_json::from_str(json) {
+        Ok(v) => Ok(v),
+        Err(e) => Err(JsonMappingsError::JsonError(e)),
+    }
+}
+
+/// Builds mappings from the `ClassesInfo.json`, `StructsInfo.json` and `EnumsInfo.json` files
+/// Dumper-7 writes for Dumpspace.
+///
+/// Dumpspace has no property indices, so properties are numbered by offset. Class and struct
+/// names lose their C++ prefix (`AActor` becomes `Actor`), enum names are kept as they are.
+/// `TEnumAsByte` and enum members become enum properties over their enum's underlying type.
+/// The core classes Dumper-7 describes with engine layout members, such as `UObject`, get no
+/// properties.
+pub fn parse_dumpspace_mappings(
+    classes: &str,
+    structs: &str,
+    enums: &str,
+) -> Result<TypeMappings, JsonMappingsError> {
+    let enums_root = parse_root(enums)?;
+    let mut enum_values = HashMap::new();
+    let mut underlying = HashMap::new();
+    for (name, location, value) in entries(&enums_root, "EnumsInfo")? {
+        let name = name.to_string();
+        let (values, inner) = parse_enum(value, &location)?;
+        if enum_values.contains_key(&name) {
+            return Err(JsonMappingsError::DuplicateName(name));
+        }
+        underlying.insert(name.clone(), inner);
+        enum_values.insert(name, values);
+    }
+
+    let mut types = HashMap::new();
+    for (file, json) in [("ClassesInfo", classes), ("StructsInfo", structs)] {
+        let root = parse_root(json)?;
+        for (name, location, value) in entries(&root, file)? {
+            let s = parse_struct(name, value, &location, &underlying)?;
+            if types.contains_key(&s.name) {
+                return Err(JsonMappingsError::DuplicateName(s.name));
+            }
+            types.insert(s.name.clone(), s);
+        }
+    }
+    Ok(TypeMappings::new(types, enum_values))
+}
+
+/// Reads the Dumpspace files from `dir`, see `parse_dumpspace_mappings`.
+pub fn read_dumpspace_mappings(dir: &str) -> Result<TypeMappings, JsonMappingsError> {
+    let mut files = Vec::with_capacity(3);
+    for name in ["ClassesInfo.json", "StructsInfo.json", "EnumsInfo.json"] {
+        match fs::read_to_string(Path::new(dir).join(name)) {
+            Ok(s) => files.push(s),
+            Err(e) => return Err(JsonMappingsError::ReadError(e)),
+        }
+    }
+    parse_dumpspace_mappings(&files[0], &files[1], &files[2])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_dumpspace_mappings;
+    use crate::mappings::{
+        EUsmapCompressionMethod, EUsmapVersion, JsonMappingsError, UsmapProvider, UsmapWriter,
+    };
+
+    // Trimmed down from the files Dumper-7 writes for a UE5 game
+    const CLASSES: &str = r#"{"updated_at":"1718123456789","data":[
+        {"UObject":[{"__InheritInfo":[]},{"__MDKClassSize":40},
+            {"VTable":[["void","D","**",[]],0,8,1]},{"Flags":[["EObjectFlags","E","",[]],8,4,1]},
+            {"Index":[["int32","D","",[]],12,4,1]},{"Class":[["UClass","C","*",[]],16,8,1]},
+            {"Name":[["FName","S","",[]],24,8,1]},{"Outer":[["UObject","C","*",[]],32,8,1]}]},
+        {"AActor":[{"__InheritInfo":["UObject"]},{"__MDKClassSize":664},
+            {"PrimaryActorTick":[["FActorTickFunction","S","",[]],40,48,1]},
+            {"bNetTemporary":[["bool","D","",[]],88,1,1,0]},
+            {"bOnlyRelevantToOwner":[["bool","D","",[]],88,1,1,2]},
+            {"bHidden":[["bool","D","",[]],88,1,1,1]},
+            {"RemoteRole":[["TEnumAsByte","D","",[["ENetRole","E","",[]]]],89,1,1]},
+            {"Owner":[["AActor","C","*",[]],96,8,1]},
+            {"Tags":[["TArray","C","",[["FName","S","",[]]]],104,16,1]}]},
+        {"APawn":[{"__InheritInfo":["AActor","UObject"]},{"__MDKClassSize":808},
+            {"AutoPossessAI":[["EAutoPossessAI","E","",[]],672,1,1]},
+            {"Slots":[["int32","D","",[]],680,4,3]},
+            {"AIControllerClass":[["TSubclassOf","C","",[["AController","C","",[]]]],696,8,1]},
+            {"Scores":[["TMap","C","",[["FName","S","",[]],["FVector","S","",[]]]],704,80,1]}]}
+    ],"version":10201}"#;
+
+    const STRUCTS: &str = r#"{"updated_at":"1718123456789","data":[
+        {"FVector":[{"__InheritInfo":[]},{"__MDKClassSize":24},
+            {"X":[["double","D","",[]],0,8,1]},{"Y":[["double","D","",[]],8,8,1]},
+            {"Z":[["double","D","",[]],16,8,1]}]},
+        {"FTickFunction":[{"__InheritInfo":[]},{"__MDKClassSize":40},
+            {"TickGroup":[["TEnumAsByte","D","",[["ETickingGroup","E","",[]]]],8,1,1]}]},
+        {"FActorTickFunction":[{"__InheritInfo":["FTickFunction"]},{"__MDKClassSize":48}]}
+    ],"version":10201}"#;
+
+    const ENUMS: &str = r#"{"updated_at":"1718123456789","data":[
+        {"ENetRole":[[{"ROLE_None":0},{"ROLE_SimulatedProxy":1},{"ROLE_Authority":3}],"uint8"]},
+        {"EAutoPossessAI":[[{"Disabled":0},{"PlacedInWorld":1},{"Spawned":2}],"uint8"]},
+        {"ETickingGroup":[[{"TG_PrePhysics":0},{"TG_EndPhysics":2}],"uint8"]},
+        {"EObjectFlags":[[{"RF_NoFlags":0},{"RF_Public":1}],"int32"]}
+    ],"version":10201}"#;
+
+    #[test]
+    fn imports_dumpspace_files() {
+        let mappings = parse_dumpspace_mappings(CLASSES, STRUCTS, ENUMS).unwrap();
+        assert_eq!(mappings.find_struct("Object").unwrap().property_count, 0);
+
+        let actor = mappings.find_struct("Actor").unwrap();
+        assert_eq!(actor.super_type.as_deref(), Some("Object"));
+        assert_eq!(actor.property_count, 7);
+        // Bitfields are ordered by bit
+        let names: Vec<_> = (0..7)
+            .map(|i| actor.property_by_index(i).unwrap().name.clone())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "PrimaryActorTick",
+                "bNetTemporary",
+                "bHidden",
+                "bOnlyRelevantToOwner",
+                "RemoteRole",
+                "Owner",
+                "Tags"
+            ]
+        );
+        let role = &actor.find_property("RemoteRole").unwrap().mapping_type;
+        assert_eq!(role.f_type, "EnumProperty");
+        assert_eq!(role.enum_name.as_deref(), Some("ENetRole"));
+        let tick = &actor
+            .find_property("PrimaryActorTick")
+            .unwrap()
+            .mapping_type;
+        assert_eq!(tick.struct_type.as_deref(), Some("ActorTickFunction"));
+        let tags = &actor.find_property("Tags").unwrap().mapping_type;
+        assert_eq!(tags.inner_type.as_ref().unwrap().f_type, "NameProperty");
+
+        let pawn = mappings.find_struct("Pawn").unwrap();
+        assert_eq!(pawn.property_count, 6);
+        assert_eq!(pawn.property_by_index(1).unwrap().name, "Slots");
+        assert_eq!(pawn.property_by_index(3).unwrap().name, "Slots");
+        assert_eq!(pawn.property_by_index(4).unwrap().name, "AIControllerClass");
+        assert_eq!(pawn.property_by_index(12).unwrap().name, "Tags");
+        let possess = &pawn.find_property("AutoPossessAI").unwrap().mapping_type;
+        assert_eq!(possess.inner_type.as_ref().unwrap().f_type, "ByteProperty");
+        let scores = &pawn.find_property("Scores").unwrap().mapping_type;
+        assert_eq!(scores.f_type, "MapProperty");
+        let value = scores.value_type.as_ref().unwrap();
+        assert_eq!(value.struct_type.as_deref(), Some("Vector"));
+        assert_eq!(
+            mappings
+                .find_struct("ActorTickFunction")
+                .unwrap()
+                .super_type
+                .as_deref(),
+            Some("TickFunction")
+        );
+        assert_eq!(
+            mappings.enum_value_name("ENetRole", 3),
+            Some("ROLE_Authority")
+        );
+
+        let writer = UsmapWriter::new(EUsmapVersion::LATEST, EUsmapCompressionMethod::None);
+        let data = writer.to_bytes(&mappings, None).unwrap();
+        let loaded = UsmapProvider::from_bytes(&data, None)
+            .unwrap()
+            .mappings_for_game;
+        assert_eq!(loaded.property_by_index("Pawn", 12).unwrap().name, "Tags");
+    }
+
+    #[test]
+    fn reports_where_the_files_are_wrong() {
+        let enums = r#"{"data":[{"ENetRole":[[{"ROLE_None":0},{"ROLE_Other":0}],"uint8"]}]}"#;
+        match parse_dumpspace_mappings(r#"{"data":[]}"#, r#"{"data":[]}"#, enums) {
+            Err(JsonMappingsError::DuplicateValue(f)) => {
+                assert_eq!(f, "EnumsInfo.data[0].ENetRole[0][1].ROLE_Other")
+            }
+            other => panic!("unexpected {:?}", other.err()),
+        }
+
+        let structs = r#"{"data":[{"FBox":[{"Min":[["FVector","X","",[]],0,24,1]}]}]}"#;
+        match parse_dumpspace_mappings(r#"{"data":[]}"#, structs, r#"{"data":[]}"#) {
+            Err(JsonMappingsError::InvalidField(f)) => {
+                assert_eq!(f, "StructsInfo.data[0].FBox.Min[0]")
+            }
+            other => panic!("unexpected {:?}", other.err()),
+        }
+
+        let classes = r#"{"data":[{"AActor":[{"Tags":[["TArray","C","",[]],0,16,1]}]}]}"#;
+        match parse_dumpspace_mappings(classes, r#"{"data":[]}"#, r#"{"data":[]}"#) {
+            Err(JsonMappingsError::MissingField(f)) => {
+                assert_eq!(f, "ClassesInfo.data[0].AActor.Tags[0][3][0]")
+            }
+            other => panic!("unexpected {:?}", other.err()),
+        }
+    }
+}
@@ -73,4 +73,42 @@ impl EPropertyType {
             _ => EPropertyType::Unknown,
         }
     }
+
+    /// Inverse of the `Debug` name stored in `PropertyType::f_type`.
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "ByteProperty" => EPropertyType::ByteProperty,
+            "BoolProperty" => EPropertyType::BoolProperty,
+            "IntProperty" => EPropertyType::IntProperty,
+            "FloatProperty" => EPropertyType::FloatProperty,
+            "ObjectProperty" => EPropertyType::ObjectProperty,
+            "NameProperty" => EPropertyType::NameProperty,
+            "DelegateProperty" => EPropertyType::DelegateProperty,
+            "DoubleProperty" => EPropertyType::DoubleProperty,
+            "ArrayProperty" => EPropertyType::ArrayProperty,
+            "StructProperty" => EPropertyType::StructProperty,
+            "StrProperty" => EPropertyType::StrProperty,
+            "TextProperty" => EPropertyType::TextProperty,
+            "InterfaceProperty" => EPropertyType::InterfaceProperty,
+            "MulticastDelegateProperty" => EPropertyType::MulticastDelegateProperty,
+            "WeakObjectProperty" => EPropertyType::WeakObjectProperty,
+            "LazyObjectProperty" => EPropertyType::LazyObjectProperty,
+            "AssetObjectProperty" => EPropertyType::AssetObjectProperty,
+            "SoftObjectProperty" => EPropertyType::SoftObjectProperty,
+            "UInt64Property" => EPropertyType::UInt64Property,
+            "UInt32Property" => EPropertyType::UInt32Property,
+            "UInt16Property" => EPropertyType::UInt16Property,
+            "Int64Property" => EPropertyType::Int64Property,
+            "Int16Property" => EPropertyType::Int16Property,
+            "Int8Property" => EPropertyType::Int8Property,
+            "MapProperty" => EPropertyType::MapProperty,
+            "SetProperty" => EPropertyType::SetProperty,
+            "EnumProperty" => EPropertyType::EnumProperty,
+            "FieldPathProperty" => EPropertyType::FieldPathProperty,
+            "OptionalProperty" => EPropertyType::OptionalProperty,
+            "Utf8StrProperty" => EPropertyType::Utf8StrProperty,
+            "AnsiStrProperty" => EPropertyType::AnsiStrProperty,
+            _ => EPropertyType::Unknown,
+        }
+    }
 }
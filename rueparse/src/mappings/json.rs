@@ -0,0 +1,445 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+
+use serde_json::Value;
+
+use super::{EPropertyType, PropertyInfo, PropertyType, Struct, TypeMappings};
+
+#[derive(Debug)]
+pub enum JsonMappingsError {
+    ReadError(io::Error),
+    JsonError(serde_json::Error),
+    MissingField(String),
+    InvalidField(String),
+    /// Two structs, classes or enums with the same name.
+    DuplicateName(String),
+    /// A property whose index, or one of its static array elements, is already taken.
+    DuplicateIndex(String),
+    /// An enum entry whose value is already taken by another entry.
+    DuplicateValue(String),
+}
+
+fn field<'a>(value: &'a Value, name: &str, location: &str) -> Result<&'a Value, JsonMappingsError> {
+    match value.get(name) {
+        Some(v) if !v.is_null() => Ok(v),
+        _ => Err(JsonMappingsError::MissingField(format!(
+            "{}{}",
+            location, name
+        ))),
+    }
+}
+
+fn get_str<'a>(value: &'a Value, name: &str, location: &str) -> Result<&'a str, JsonMappingsError> {
+    match field(value, name, location)? {
+        Value::String(s) => Ok(s),
+        _ => Err(JsonMappingsError::InvalidField(format!(
+            "{}{}",
+            location, name
+        ))),
+    }
+}
+
+/// Missing and null fields give `None`.
+fn get_i64(value: &Value, name: &str, location: &str) -> Result<Option<i64>, JsonMappingsError> {
+    match value.get(name) {
+        Some(Value::Null) | None => Ok(None),
+        Some(v) => match v.as_i64() {
+            Some(i) => Ok(Some(i)),
+            None => Err(JsonMappingsError::InvalidField(format!(
+                "{}{}",
+                location, name
+            ))),
+        },
+    }
+}
+
+fn get_array<'a>(value: &'a Value, name: &str) -> Result<&'a [Value], JsonMappingsError> {
+    match value.get(name) {
+        Some(Value::Array(a)) => Ok(a),
+        Some(Value::Null) | None => Ok(&[]),
+        Some(_) => Err(JsonMappingsError::InvalidField(name.to_string())),
+    }
+}
+
+/// Accepts `IntProperty` as well as the short `Int`.
+fn parse_type_name(name: &str, location: &str) -> Result<EPropertyType, JsonMappingsError> {
+    match EPropertyType::from_name(name) {
+        EPropertyType::Unknown => match EPropertyType::from_name(&format!("{}Property", name)) {
+            EPropertyType::Unknown => Err(JsonMappingsError::InvalidField(location.to_string())),
+            t => Ok(t),
+        },
+        t => Ok(t),
+    }
+}
+
+fn parse_type(value: &Value, location: &str) -> Result<PropertyType, JsonMappingsError> {
+    let type_enum = match value {
+        Value::String(s) => parse_type_name(s, location.trim_end_matches('.'))?,
+        Value::Object(_) => parse_type_name(
+            get_str(value, "type", location)?,
+            &format!("{}type", location),
+        )?,
+        _ => {
+            let location = location.trim_end_matches('.').to_string();
+            return Err(JsonMappingsError::InvalidField(location));
+        }
+    };
+    let child = |name: &str| -> Result<Option<Box<PropertyType>>, JsonMappingsError> {
+        let child = field(value, name, location)?;
+        let child = parse_type(child, &format!("{}{}.", location, name))?;
+        Ok(Some(Box::new(child)))
+    };
+
+    let mut struct_type = None;
+    let mut inner_type = None;
+    let mut value_type = None;
+    let mut enum_name = None;
+    match type_enum {
+        EPropertyType::EnumProperty => {
+            // Enums without an explicit underlying type are bytes
+            inner_type = match value.get("inner") {
+                Some(Value::Null) | None => Some(Box::new(parse_type(
+                    &Value::from("ByteProperty"),
+                    location,
+                )?)),
+                Some(_) => child("inner")?,
+            };
+            enum_name = Some(get_str(value, "enum", location)?.to_string());
+        }
+        EPropertyType::StructProperty => {
+            struct_type = Some(get_str(value, "struct", location)?.to_string());
+        }
+        EPropertyType::SetProperty
+        | EPropertyType::ArrayProperty
+        | EPropertyType::OptionalProperty => {
+            inner_type = child("inner")?;
+        }
+        EPropertyType::MapProperty => {
+            inner_type = child("inner")?;
+            value_type = child("value")?;
+        }
+        _ => {}
+    }
+    Ok(PropertyType::new(
+        format!("{:?}", type_enum),
+        struct_type,
+        inner_type,
+        value_type,
+        enum_name,
+        None,
+        None,
+    ))
+}
+
+fn parse_struct(value: &Value, location: &str) -> Result<Struct, JsonMappingsError> {
+    let name = get_str(value, "name", location)?.to_string();
+    let super_type = match value.get("super") {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Null) | None => String::new(),
+        Some(_) => {
+            return Err(JsonMappingsError::InvalidField(format!(
+                "{}super",
+                location
+            )));
+        }
+    };
+
+    let mut properties = HashMap::new();
+    // Indices and the property count default to counting up in the listed order
+    let mut next_index: i64 = 0;
+    let list = match value.get("properties") {
+        Some(Value::Array(a)) => a.as_slice(),
+        Some(Value::Null) | None => &[],
+        Some(_) => {
+            return Err(JsonMappingsError::InvalidField(format!(
+                "{}properties",
+                location
+            )));
+        }
+    };
+    for (i, property) in list.iter().enumerate() {
+        let location = format!("{}properties[{}].", location, i);
+        let property_name = get_str(property, "name", &location)?.to_string();
+        let index = get_i64(property, "index", &location)?.unwrap_or(next_index);
+        let index = match i32::try_from(index) {
+            Ok(i) if i >= 0 => i,
+            _ => {
+                return Err(JsonMappingsError::InvalidField(format!(
+                    "{}index",
+                    location
+                )));
+            }
+        };
+        let array_size = get_i64(property, "arraySize", &location)?.unwrap_or(1);
+        let array_size = match u8::try_from(array_size) {
+            Ok(s) if s > 0 => s,
+            _ => {
+                return Err(JsonMappingsError::InvalidField(format!(
+                    "{}arraySize",
+                    location
+                )));
+            }
+        };
+        // The property count past the last element has to fit as well
+        if index.checked_add(array_size as i32).is_none() {
+            return Err(JsonMappingsError::InvalidField(format!(
+                "{}index",
+                location
+            )));
+        }
+        let property_type = parse_type(
+            field(property, "type", &location)?,
+            &format!("{}type.", location),
+        )?;
+        let info = PropertyInfo::new(index, property_name, property_type, Some(array_size));
+        for element in 0..array_size as i32 {
+            let mut clone = info.clone();
+            clone.index = element;
+            if properties.insert(index + element, clone).is_some() {
+                return Err(JsonMappingsError::DuplicateIndex(format!(
+                    "{}index",
+                    location
+                )));
+            }
+        }
+        next_index = next_index.max(index as i64 + array_size as i64);
+    }
+
+    // The count has to cover every index
+    let property_count = match get_i64(value, "propertyCount", location)? {
+        Some(c) => match i32::try_from(c) {
+            Ok(c) if c as i64 >= next_index => c,
+            _ => {
+                return Err(JsonMappingsError::InvalidField(format!(
+                    "{}propertyCount",
+                    location
+                )));
+            }
+        },
+        None => next_index as i32,
+    };
+    Ok(Struct::new_with_super(
+        name,
+        Some(super_type),
+        properties,
+        property_count,
+    ))
+}
+
+/// Values are either an object of names to values or an array of names numbered from 0.
+fn parse_enum(
+    value: &Value,
+    location: &str,
+) -> Result<(String, HashMap<i64, String>), JsonMappingsError> {
+    let name = get_str(value, "name", location)?.to_string();
+    let mut values = HashMap::new();
+    match field(value, "values", location)? {
+        Value::Object(entries) => {
+            for (entry, v) in entries {
+                let taken = match v.as_i64() {
+                    Some(v) => values.insert(v, entry.clone()).is_some(),
+                    None => {
+                        return Err(JsonMappingsError::InvalidField(format!(
+                            "{}values.{}",
+                            location, entry
+                        )));
+                    }
+                };
+                if taken {
+                    return Err(JsonMappingsError::DuplicateValue(format!(
+                        "{}values.{}",
+                        location, entry
+                    )));
+                }
+            }
+        }
+        Value::Array(entries) => {
+            for (i, entry) in entries.iter().enumerate() {
+                match entry {
+                    Value::String(s) => values.insert(i as i64, s.clone()),
+                    _ => {
+                        return Err(JsonMappingsError::InvalidField(format!(
+                            "{}values[{}]",
+                            location, i
+                        )));
+                    }
+                };
+            }
+        }
+        _ => {
+            return Err(JsonMappingsError::InvalidField(format!(
+                "{}values",
+                location
+            )));
+        }
+    }
+    Ok((name, values))
+}
+
+/// Builds mappings from a JSON SDK dump in this layout, where every field except the names,
+/// property types and enum values is optional:
+///
+/// ```json
+/// {
+///   "classes": [{ "name": "Pawn", "super": "Actor", "propertyCount": 3, "properties": [
+///     { "name": "Slots", "index": 0, "arraySize": 2, "type": "IntProperty" },
+///     { "name": "Tags", "type": { "type": "ArrayProperty", "inner": "NameProperty" } }
+///   ] }],
+///   "structs": [{ "name": "Vector", "properties": [{ "name": "X", "type": "Double" }] }],
+///   "enums": [{ "name": "EColor", "values": { "EColor::Red": 0, "EColor::Blue": 2 } }]
+/// }
+/// ```
+///
+/// Type objects hold `inner` for arrays, sets, optionals and enums, `inner` and `value` for maps,
+/// `struct` for structs and `enum` for enums. Type names may leave out the `Property` suffix.
+/// Indices default to following the previous property and the property count to the end of the
+/// last one. Indices must not overlap and enum values must be unique.
+///
+/// For Dumper-7's Dumpspace files see `parse_dumpspace_mappings`.
+pub fn parse_json_mappings(json: &str) -> Result<TypeMappings, JsonMappingsError> {
+    let root: Value = match serde_json::from_str(json) {
+        Ok(v) => v,
+        Err(e) => return Err(JsonMappingsError::JsonError(e)),
+    };
+
+    let mut types = HashMap::new();
+    for list in ["classes", "structs"] {
+        for (i, value) in get_array(&root, list)?.iter().enumerate() {
+            let s = parse_struct(value, &format!("{}[{}].", list, i))?;
+            if types.contains_key(&s.name) {
+                return Err(JsonMappingsError::DuplicateName(s.name));
+            }
+            types.insert(s.name.clone(), s);
+        }
+    }
+
+    let mut enums = HashMap::new();
+    for (i, value) in get_array(&root, "enums")?.iter().enumerate() {
+        let (name, values) = parse_enum(value, &format!("enums[{}].", i))?;
+        if enums.contains_key(&name) {
+            return Err(JsonMappingsError::DuplicateName(name));
+        }
+        enums.insert(name, values);
+    }
+    Ok(TypeMappings::new(types, enums))
+}
+
+pub fn read_json_mappings(path: &str) -> Result<TypeMappings, JsonMappingsError> {
+    match fs::read_to_string(path) {
+        Ok(s) => parse_json_mappings(&s),
+        Err(e) => Err(JsonMappingsError::ReadError(e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{JsonMappingsError, parse_json_mappings};
+    use crate::mappings::{EUsmapCompressionMethod, EUsmapVersion, UsmapProvider, UsmapWriter};
+
+    const DUMP: &str = r#"{
+        "classes": [
+            { "name": "Pawn", "super": "Actor", "propertyCount": 5, "properties": [
+                { "name": "Slots", "arraySize": 2, "type": "Int" },
+                { "name": "Color", "type": { "type": "EnumProperty", "enum": "EColor" } },
+                { "name": "Scores", "index": 4, "type": {
+                    "type": "Map", "inner": "NameProperty",
+                    "value": { "type": "Array", "inner": { "type": "Struct", "struct": "Vector" } }
+                } }
+            ] },
+            { "name": "Actor", "properties": [{ "name": "Tags", "type": { "type": "Set", "inner": "Name" } }] }
+        ],
+        "structs": [{ "name": "Vector", "properties": [
+            { "name": "X", "type": "Double" }, { "name": "Y", "type": "Double" }
+        ] }],
+        "enums": [
+            { "name": "EColor", "values": { "EColor::Red": 0, "EColor::Blue": 2 } },
+            { "name": "EShape", "values": ["EShape::Box", "EShape::Ball"] }
+        ]
+    }"#;
+
+    #[test]
+    fn imports_dump_and_saves_as_usmap() {
+        let mappings = parse_json_mappings(DUMP).unwrap();
+        let pawn = mappings.find_struct("Pawn").unwrap();
+        assert_eq!(pawn.property_count, 5);
+        assert_eq!(pawn.property_by_index(1).unwrap().name, "Slots");
+        assert_eq!(pawn.property_by_index(2).unwrap().name, "Color");
+        assert_eq!(pawn.property_by_index(5).unwrap().name, "Tags");
+        let scores = &pawn.find_property("scores").unwrap().mapping_type;
+        let value = scores.value_type.as_ref().unwrap();
+        let inner = value.inner_type.as_ref().unwrap();
+        assert_eq!(inner.struct_type.as_deref(), Some("Vector"));
+        let color = &pawn.find_property("Color").unwrap().mapping_type;
+        assert_eq!(color.inner_type.as_ref().unwrap().f_type, "ByteProperty");
+        assert_eq!(mappings.find_struct("Vector").unwrap().property_count, 2);
+        assert_eq!(mappings.enum_value_name("EColor", 2), Some("EColor::Blue"));
+        assert_eq!(mappings.enum_value_name("EShape", 1), Some("EShape::Ball"));
+
+        let writer = UsmapWriter::new(EUsmapVersion::LATEST, EUsmapCompressionMethod::Brotli);
+        let data = writer.to_bytes(&mappings, None).unwrap();
+        let loaded = UsmapProvider::from_bytes(&data, None)
+            .unwrap()
+            .mappings_for_game;
+        assert_eq!(writer.to_bytes(&loaded, None).unwrap(), data);
+        assert_eq!(loaded.property_by_index("Pawn", 5).unwrap().name, "Tags");
+    }
+
+    #[test]
+    fn reports_where_the_dump_is_wrong() {
+        let json = r#"{ "structs": [{ "name": "A", "properties": [
+            { "name": "B", "type": { "type": "Array" } }
+        ] }] }"#;
+        match parse_json_mappings(json) {
+            Err(JsonMappingsError::MissingField(f)) => {
+                assert_eq!(f, "structs[0].properties[0].type.inner")
+            }
+            other => panic!("unexpected {:?}", other.err()),
+        }
+
+        let json = r#"{ "structs": [{ "name": "A", "properties": [{ "name": "B", "type": "Float32" }] }] }"#;
+        match parse_json_mappings(json) {
+            Err(JsonMappingsError::InvalidField(f)) => {
+                assert_eq!(f, "structs[0].properties[0].type")
+            }
+            other => panic!("unexpected {:?}", other.err()),
+        }
+
+        // The second element of A lands on B
+        let json = r#"{ "structs": [{ "name": "S", "properties": [
+            { "name": "A", "arraySize": 2, "type": "Int" },
+            { "name": "B", "index": 1, "type": "Int" }
+        ] }] }"#;
+        match parse_json_mappings(json) {
+            Err(JsonMappingsError::DuplicateIndex(f)) => {
+                assert_eq!(f, "structs[0].properties[1].index")
+            }
+            other => panic!("unexpected {:?}", other.err()),
+        }
+
+        // The second element would be past i32::MAX
+        let json = r#"{ "structs": [{ "name": "S", "properties": [
+            { "name": "A", "index": 2147483647, "arraySize": 2, "type": "Int" }
+        ] }] }"#;
+        match parse_json_mappings(json) {
+            Err(JsonMappingsError::InvalidField(f)) => {
+                assert_eq!(f, "structs[0].properties[0].index")
+            }
+            other => panic!("unexpected {:?}", other.err()),
+        }
+
+        let json = r#"{ "structs": [{ "name": "S", "propertyCount": 1, "properties": [
+            { "name": "A", "index": 1, "type": "Int" }
+        ] }] }"#;
+        match parse_json_mappings(json) {
+            Err(JsonMappingsError::InvalidField(f)) => assert_eq!(f, "structs[0].propertyCount"),
+            other => panic!("unexpected {:?}", other.err()),
+        }
+
+        let json = r#"{ "enums": [{ "name": "E", "values": { "E::A": 1, "E::B": 1 } }] }"#;
+        match parse_json_mappings(json) {
+            Err(JsonMappingsError::DuplicateValue(f)) => assert_eq!(f, "enums[0].values.E::B"),
+            other => panic!("unexpected {:?}", other.err()),
+        }
+    }
+}
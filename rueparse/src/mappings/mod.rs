@@ -1,72 +1,232 @@
+use crate::compression::{CompressionMethod, DecompressionError, decompress};
+use crate::errors;
 use crate::readers::FUsmapReader;
 use crate::readers::FileReader;
 use crate::readers::Reader;
-use brotli;
-use compression::EUsmapCompressionMethod;
 use oodle;
 use oodle::Oodle;
-use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::{self, Cursor, Read};
-use std::rc::Rc;
 use std::string::FromUtf8Error;
+use std::sync::Arc;
 
 use crate::objects::core::serialization::FCustomVersionContainer;
 
+mod codegen;
 mod compression;
+mod diff;
+mod dumpspace;
 mod epropertytype;
+mod json;
 mod properties;
 mod version;
+mod writer;
 
+pub use codegen::*;
+pub use compression::EUsmapCompressionMethod;
+pub use diff::*;
+pub use dumpspace::*;
 pub use epropertytype::*;
+pub use json::*;
 pub use properties::*;
 pub use version::*;
+pub use writer::*;
 
 #[derive(Debug)]
 pub enum UsmapParserError {
     InvalidMagic,
+    UnsupportedVersion(u8),
     CompressionSizeEquality,
     OodleNotFound,
     InvalidCompressionMethod,
+    DecompressionError(DecompressionError),
     ReadError(io::Error),
     FromUtf8Error(FromUtf8Error),
     FileOpenError(io::Error),
+    /// A name index past the end of the name table, at an offset into the decompressed data.
+    NameIndexOutOfRange {
+        index: i32,
+        offset: u64,
+    },
+    /// The decompressed data ended inside the value starting at `offset`.
+    Truncated {
+        offset: u64,
+    },
+    /// Property types nested past `PropertyType::MAX_DEPTH`, at the offset of the first type
+    /// too deep.
+    TooDeeplyNested {
+        offset: u64,
+    },
 }
 
+/// Mappings loaded from a .usmap file. The Oodle handle is only needed for Oodle compressed
+/// files.
 #[derive(Debug)]
 pub struct UsmapProvider {
     pub mappings_for_game: TypeMappings,
 }
 
 impl UsmapProvider {
-    pub fn from_path(path: &str, oo: &Oodle) -> Result<Self, UsmapParserError> {
+    pub fn from_path(path: &str, oo: Option<&Oodle>) -> Result<Self, UsmapParserError> {
         let file = match File::open(path) {
             Ok(f) => f,
             Err(e) => return Err(UsmapParserError::FileOpenError(e)),
         };
         let mut reader = FileReader::new(file);
-        let usmap = match UsmapParser::from_reader(&mut reader, Some(&oo)) {
+        let usmap = match UsmapParser::from_reader(&mut reader, oo) {
             Ok(u) => u,
+            Err(UsmapParserError::ReadError(e)) => {
+                return Err(UsmapParserError::ReadError(errors::in_file(e, path)));
+            }
             Err(e) => return Err(e),
         };
         Ok(Self {
             mappings_for_game: usmap.mappings,
         })
     }
+
+    pub fn from_bytes(data: &[u8], oo: Option<&Oodle>) -> Result<Self, UsmapParserError> {
+        let mut reader = FileReader::new(Cursor::new(data));
+        let usmap = match UsmapParser::from_reader(&mut reader, oo) {
+            Ok(u) => u,
+            Err(e) => return Err(e),
+        };
+        Ok(Self {
+            mappings_for_game: usmap.mappings,
+        })
+    }
+
+    /// See `parse_json_mappings` for the layout.
+    pub fn from_json(json: &str) -> Result<Self, JsonMappingsError> {
+        match parse_json_mappings(json) {
+            Ok(m) => Ok(Self {
+                mappings_for_game: m,
+            }),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn from_json_path(path: &str) -> Result<Self, JsonMappingsError> {
+        match read_json_mappings(path) {
+            Ok(m) => Ok(Self {
+                mappings_for_game: m,
+            }),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Loads the Dumper-7 Dumpspace files in `dir`.
+    pub fn from_dumpspace_dir(dir: &str) -> Result<Self, JsonMappingsError> {
+        match read_dumpspace_mappings(dir) {
+            Ok(m) => Ok(Self {
+                mappings_for_game: m,
+            }),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Reads `read` to the end first, so it does not need to seek.
+    pub fn from_read(mut read: impl Read, oo: Option<&Oodle>) -> Result<Self, UsmapParserError> {
+        let mut data = Vec::new();
+        if let Err(e) = read.read_to_end(&mut data) {
+            return Err(UsmapParserError::ReadError(e));
+        }
+        Self::from_bytes(&data, oo)
+    }
 }
 
+/// Structs and enums by name. Built once and never modified, so clones are cheap and can be
+/// shared between threads.
 #[derive(Debug, Clone)]
 pub struct TypeMappings {
-    pub types: Rc<RefCell<HashMap<String, Box<Struct>>>>,
-    pub enums: Rc<RefCell<HashMap<String, HashMap<i32, String>>>>,
+    pub types: Arc<HashMap<String, Arc<Struct>>>,
+    pub enums: Arc<HashMap<String, HashMap<i64, String>>>,
+    /// Lowercase names to the names in `types` and `enums`.
+    struct_names: Arc<HashMap<String, String>>,
+    enum_names: Arc<HashMap<String, String>>,
 }
 impl TypeMappings {
+    /// Links every struct to its super struct by name, so the order they were loaded in does not
+    /// matter. Missing supers and cycles leave `super_struct` empty.
     pub fn new(
-        types: Rc<RefCell<HashMap<String, Box<Struct>>>>,
-        enums: Rc<RefCell<HashMap<String, HashMap<i32, String>>>>,
+        types: HashMap<String, Struct>,
+        enums: HashMap<String, HashMap<i64, String>>,
     ) -> Self {
-        Self { types, enums }
+        let names: Vec<String> = types.keys().cloned().collect();
+        let mut pending = types;
+        let mut resolved = HashMap::with_capacity(names.len());
+        for name in names {
+            Self::resolve(&name, &mut pending, &mut resolved);
+        }
+        let lowercase = |names: Vec<&String>| -> HashMap<String, String> {
+            names
+                .into_iter()
+                .map(|n| (n.to_ascii_lowercase(), n.clone()))
+                .collect()
+        };
+        Self {
+            struct_names: Arc::new(lowercase(resolved.keys().collect())),
+            enum_names: Arc::new(lowercase(enums.keys().collect())),
+            types: Arc::new(resolved),
+            enums: Arc::new(enums),
+        }
+    }
+
+    /// Struct by name, in any casing. An exact match wins over names differing only in case.
+    pub fn find_struct(&self, name: &str) -> Option<&Struct> {
+        match self.types.get(name) {
+            Some(s) => Some(s),
+            None => {
+                let name = self.struct_names.get(&name.to_ascii_lowercase())?;
+                self.types.get(name).map(|s| s.as_ref())
+            }
+        }
+    }
+
+    /// See `Struct::find_property`.
+    pub fn find_property(&self, struct_name: &str, name: &str) -> Option<&PropertyInfo> {
+        self.find_struct(struct_name)?.find_property(name)
+    }
+
+    /// See `Struct::property_by_index`.
+    pub fn property_by_index(&self, struct_name: &str, index: i32) -> Option<&PropertyInfo> {
+        self.find_struct(struct_name)?.property_by_index(index)
+    }
+
+    pub fn find_enum(&self, name: &str) -> Option<&HashMap<i64, String>> {
+        match self.enums.get(name) {
+            Some(e) => Some(e),
+            None => self
+                .enums
+                .get(self.enum_names.get(&name.to_ascii_lowercase())?),
+        }
+    }
+
+    pub fn enum_value_name(&self, enum_name: &str, value: i64) -> Option<&str> {
+        self.find_enum(enum_name)?.get(&value).map(|n| n.as_str())
+    }
+
+    /// A struct is taken out of `pending` before its super is resolved, so a cycle finds it
+    /// missing and stops there.
+    fn resolve(
+        name: &str,
+        pending: &mut HashMap<String, Struct>,
+        resolved: &mut HashMap<String, Arc<Struct>>,
+    ) -> Option<Arc<Struct>> {
+        if let Some(s) = resolved.get(name) {
+            return Some(Arc::clone(s));
+        }
+        let mut s = pending.remove(name)?;
+        s.super_struct = match &s.super_type {
+            Some(super_name) if !super_name.is_empty() => {
+                Self::resolve(super_name, pending, resolved)
+            }
+            _ => None,
+        };
+        let s = Arc::new(s);
+        resolved.insert(name.to_string(), Arc::clone(&s));
+        Some(s)
     }
 }
 
@@ -80,15 +240,6 @@ pub struct UsmapParser {
     netcl: u32,
 }
 
-fn decompress_brotli(
-    compressed_data: &[u8],
-    decompressed_buffer: &mut Vec<u8>,
-) -> std::io::Result<()> {
-    let mut decompressor = brotli::Decompressor::new(Cursor::new(compressed_data), 4096);
-    decompressor.read_to_end(decompressed_buffer)?;
-    Ok(())
-}
-
 impl UsmapParser {
     pub fn from_reader(
         reader: &mut dyn Reader,
@@ -106,7 +257,10 @@ impl UsmapParser {
             Ok(r) => r,
             Err(e) => return Err(UsmapParserError::ReadError(e)),
         };
-        let version: EUsmapVersion = EUsmapVersion::from(version_byte);
+        let version: EUsmapVersion = match EUsmapVersion::try_from(version_byte) {
+            Ok(v) => v,
+            Err(v) => return Err(UsmapParserError::UnsupportedVersion(v)),
+        };
         let package_version: FPackageFileVersion;
         let custom_versions: FCustomVersionContainer;
         let netcl: u32;
@@ -149,11 +303,16 @@ impl UsmapParser {
             Err(e) => return Err(UsmapParserError::ReadError(e)),
         };
 
+        if let Err(e) = reader.check_limit(decomp_size as u64) {
+            return Err(UsmapParserError::ReadError(e));
+        }
+        if let Err(e) = reader.check_allocation(comp_size as u64, 1) {
+            return Err(UsmapParserError::ReadError(e));
+        }
         let mut data = vec![0u8; decomp_size as usize];
-        println!("{:?} {:?} {:?}", compression_method, comp_size, decomp_size);
         match compression_method {
             EUsmapCompressionMethod::None => {
-                if (comp_size != decomp_size) {
+                if comp_size != decomp_size {
                     return Err(UsmapParserError::CompressionSizeEquality);
                 }
                 match reader.read_exact(&mut data[..comp_size as usize]) {
@@ -161,67 +320,42 @@ impl UsmapParser {
                     Err(e) => return Err(UsmapParserError::ReadError(e)),
                 };
             }
-            EUsmapCompressionMethod::Oodle => match oo {
-                Some(o) => {
-                    let mut comp_bytes = vec![0u8; comp_size as usize];
-                    match reader.read_exact(&mut comp_bytes[..comp_size as usize]) {
-                        Ok(_) => {}
-                        Err(e) => return Err(UsmapParserError::ReadError(e)),
-                    };
-                    o.decompress(&comp_bytes, &mut data);
-                }
-                _ => {
+            EUsmapCompressionMethod::Unknown => {
+                return Err(UsmapParserError::InvalidCompressionMethod);
+            }
+            _ => {
+                if compression_method == EUsmapCompressionMethod::Oodle && oo.is_none() {
                     return Err(UsmapParserError::OodleNotFound);
                 }
-            },
-            EUsmapCompressionMethod::Brotli => {
-                let mut comp_bytes = vec![0u8; comp_size as usize];
-                match reader.read_exact(&mut comp_bytes[..comp_size as usize]) {
-                    Ok(_) => {}
-                    Err(e) => return Err(UsmapParserError::ReadError(e)),
-                };
-                match decompress_brotli(&comp_bytes, &mut data) {
-                    Ok(_) => {}
-                    Err(e) => return Err(UsmapParserError::ReadError(e)),
-                };
-            }
-            EUsmapCompressionMethod::ZStandart => {
                 let mut comp_bytes = vec![0u8; comp_size as usize];
                 match reader.read_exact(&mut comp_bytes[..comp_size as usize]) {
                     Ok(_) => {}
                     Err(e) => return Err(UsmapParserError::ReadError(e)),
                 };
-                match zstd::bulk::decompress_to_buffer(&comp_bytes, &mut data) {
+                match decompress(
+                    CompressionMethod::from(compression_method),
+                    &comp_bytes,
+                    &mut data,
+                    oo,
+                ) {
                     Ok(_) => {}
-                    Err(e) => return Err(UsmapParserError::ReadError(e)),
+                    Err(e) => return Err(UsmapParserError::DecompressionError(e)),
                 };
             }
-            _ => {
-                return Err(UsmapParserError::InvalidCompressionMethod);
-            }
         }
-        let mut reader = FUsmapReader::new(&mut data, version);
-        let name_size: u32 = match reader.read_u32() {
-            Ok(r) => r,
-            Err(e) => return Err(UsmapParserError::ReadError(e)),
-        };
+        let mut reader = FUsmapReader::with_limits(&data, version, reader.limits());
+        let name_size: u32 = reader.checked(|r| r.read_u32())?;
+        // Every name has at least a one byte length prefix
+        reader.checked(|r| r.check_allocation(name_size as u64, 1))?;
         let mut name_lut: Vec<String> = Vec::with_capacity(name_size as usize);
         for _ in 0..name_size {
             let name_length: usize = if reader.version as u8 >= EUsmapVersion::LongFName as u8 {
-                let name_byte = match reader.read_u16() {
-                    Ok(r) => r,
-                    Err(e) => return Err(UsmapParserError::ReadError(e)),
-                };
-                name_byte as usize
+                reader.checked(|r| r.read_u16())? as usize
             } else {
-                let name_byte = match reader.read_u8() {
-                    Ok(r) => r,
-                    Err(e) => return Err(UsmapParserError::ReadError(e)),
-                };
-                name_byte as usize
+                reader.checked(|r| r.read_u8())? as usize
             };
             let mut name_bytes = vec![0u8; name_length as usize];
-            reader.read_exact(&mut name_bytes);
+            reader.checked(|r| r.read_exact(&mut name_bytes))?;
             let name = match String::from_utf8(name_bytes.to_vec()) {
                 Ok(s) => s,
                 Err(e) => {
@@ -231,51 +365,38 @@ impl UsmapParser {
             name_lut.push(name);
         }
 
-        let enum_count: u32 = match reader.read_u32() {
-            Ok(r) => r,
-            Err(e) => return Err(UsmapParserError::ReadError(e)),
-        };
-        let enums: Rc<RefCell<HashMap<String, HashMap<i32, String>>>> =
-            Rc::new(RefCell::new(HashMap::new()));
+        let enum_count: u32 = reader.checked(|r| r.read_u32())?;
+        let mut enums: HashMap<String, HashMap<i64, String>> = HashMap::new();
         for _ in 0..enum_count {
-            let enum_name = reader.read_name(&name_lut);
+            let enum_name = reader.read_name(&name_lut)?;
 
-            let enum_names_length: usize = if reader.version as u8 >= EUsmapVersion::LongFName as u8
-            {
-                let name_byte = match reader.read_u16() {
-                    Ok(r) => r,
-                    Err(e) => return Err(UsmapParserError::ReadError(e)),
+            let enum_names_length: usize =
+                if reader.version as u8 >= EUsmapVersion::LargeEnums as u8 {
+                    reader.checked(|r| r.read_u16())? as usize
+                } else {
+                    reader.checked(|r| r.read_u8())? as usize
                 };
-                name_byte as usize
-            } else {
-                let name_byte = match reader.read_u8() {
-                    Ok(r) => r,
-                    Err(e) => return Err(UsmapParserError::ReadError(e)),
-                };
-                name_byte as usize
-            };
-            let mut enum_names: HashMap<i32, String> = HashMap::with_capacity(enum_names_length);
+            let explicit_values = reader.version as u8 >= EUsmapVersion::ExplicitEnumValues as u8;
+            let mut enum_names: HashMap<i64, String> = HashMap::with_capacity(enum_names_length);
             for i in 0..enum_names_length {
-                enum_names.insert(i as i32, reader.read_name(&name_lut));
+                // Older versions number the entries in order
+                let value = if explicit_values {
+                    reader.checked(|r| r.read_i64())?
+                } else {
+                    i as i64
+                };
+                enum_names.insert(value, reader.read_name(&name_lut)?);
             }
-            enums.borrow_mut().insert(enum_name, enum_names);
+            enums.insert(enum_name, enum_names);
         }
 
-        let struct_count: u32 = match reader.read_u32() {
-            Ok(r) => r,
-            Err(e) => return Err(UsmapParserError::ReadError(e)),
-        };
-        let structs: Rc<RefCell<HashMap<String, Box<Struct>>>> =
-            Rc::new(RefCell::new(HashMap::with_capacity(struct_count as usize)));
-        let mappings: Rc<RefCell<TypeMappings>> = Rc::new(RefCell::new(TypeMappings::new(
-            Rc::clone(&structs),
-            Rc::clone(&enums),
-        )));
+        let struct_count: u32 = reader.checked(|r| r.read_u32())?;
+        // Every struct has at least its name, super name and two property counts
+        reader.checked(|r| r.check_allocation(struct_count as u64, 12))?;
+        let mut structs: HashMap<String, Struct> = HashMap::with_capacity(struct_count as usize);
         for _ in 0..struct_count {
-            let s = Struct::parse(Some(Rc::clone(&mappings)), &mut reader, &name_lut);
-            structs
-                .borrow_mut()
-                .insert(s.name.clone(), Box::new(s.clone()));
+            let s = Struct::parse(&mut reader, &name_lut)?;
+            structs.insert(s.name.clone(), s);
         }
 
         Ok(Self {
@@ -284,8 +405,171 @@ impl UsmapParser {
             package_version,
             compression_method,
             custom_versions,
-            mappings: mappings.borrow_mut().clone(),
+            mappings: TypeMappings::new(structs, enums),
             netcl,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        EUsmapCompressionMethod, EUsmapVersion, PropertyInfo, PropertyType, Struct, TypeMappings,
+        UsmapParser, UsmapParserError, UsmapProvider, UsmapWriter,
+    };
+    use crate::readers::FileReader;
+    use std::collections::HashMap;
+    use std::io::Cursor;
+
+    /// Uncompressed initial version file around `data`.
+    fn usmap(data: &[u8]) -> Vec<u8> {
+        let mut file = vec![0xC4, 0x30, 0, 0];
+        file.extend((data.len() as u32).to_le_bytes());
+        file.extend((data.len() as u32).to_le_bytes());
+        file.extend(data);
+        file
+    }
+
+    fn parse(data: &[u8]) -> Result<UsmapParser, UsmapParserError> {
+        UsmapParser::from_reader(&mut FileReader::new(Cursor::new(usmap(data))), None)
+    }
+
+    #[test]
+    fn supers_resolve_regardless_of_order() {
+        let property = |name: &str| {
+            let property_type =
+                PropertyType::new("IntProperty".into(), None, None, None, None, None, None);
+            PropertyInfo::new(0, name.into(), property_type, Some(1))
+        };
+        let mut types = HashMap::new();
+        // Written sorted by name, so the child comes before its parents
+        for (name, super_type, own) in [
+            ("AChild", "BParent", "C"),
+            ("BParent", "CRoot", "B"),
+            ("CRoot", "", "A"),
+        ] {
+            let mut properties = HashMap::new();
+            properties.insert(1, property(own));
+            let s = Struct::new_with_super(name.into(), Some(super_type.into()), properties, 2);
+            types.insert(name.to_string(), s);
+        }
+        let writer = UsmapWriter::new(EUsmapVersion::LATEST, EUsmapCompressionMethod::None);
+        let data = writer
+            .to_bytes(&TypeMappings::new(types, HashMap::new()), None)
+            .unwrap();
+        let parsed = UsmapParser::from_reader(&mut FileReader::new(Cursor::new(data)), None)
+            .unwrap()
+            .mappings;
+
+        let child = &parsed.types["AChild"];
+        let chain: Vec<&str> = child.chain().map(|s| s.name.as_str()).collect();
+        assert_eq!(chain, ["AChild", "BParent", "CRoot"]);
+        assert_eq!(child.total_property_count(), 6);
+        let all: Vec<(i32, &str)> = child
+            .all_properties()
+            .map(|(i, p)| (i, p.name.as_str()))
+            .collect();
+        assert_eq!(all, [(1, "C"), (3, "B"), (5, "A")]);
+
+        assert_eq!(parsed.find_struct("achild").unwrap().name, "AChild");
+        assert!(parsed.find_struct("Missing").is_none());
+        let inherited = parsed.find_property("AChild", "a").unwrap();
+        assert_eq!(inherited.name, "A");
+        assert_eq!(inherited.mapping_type.f_type, "IntProperty");
+        assert_eq!(parsed.property_by_index("AChild", 3).unwrap().name, "B");
+        assert!(parsed.property_by_index("AChild", 4).is_none());
+        assert!(parsed.property_by_index("AChild", 6).is_none());
+        assert!(parsed.property_by_index("AChild", -1).is_none());
+    }
+
+    #[test]
+    fn enum_values_by_name() {
+        let mut enums = HashMap::new();
+        let values = [(0, "EColor::Red"), (5, "EColor::Blue")];
+        enums.insert(
+            "EColor".to_string(),
+            values.iter().map(|(v, n)| (*v, n.to_string())).collect(),
+        );
+        let mappings = TypeMappings::new(HashMap::new(), enums);
+        assert_eq!(mappings.enum_value_name("EColor", 5), Some("EColor::Blue"));
+        assert_eq!(mappings.enum_value_name("ecolor", 0), Some("EColor::Red"));
+        assert_eq!(mappings.enum_value_name("EColor", 1), None);
+        assert_eq!(mappings.enum_value_name("EShape", 0), None);
+    }
+
+    #[test]
+    fn loads_from_bytes_and_readers() {
+        let mut types = HashMap::new();
+        types.insert("Actor".to_string(), Struct::new("Actor".into(), 0));
+        let mappings = TypeMappings::new(types, HashMap::new());
+
+        let writer = UsmapWriter::new(EUsmapVersion::LATEST, EUsmapCompressionMethod::ZStandart);
+        let data = writer.to_bytes(&mappings, None).unwrap();
+        let provider = UsmapProvider::from_bytes(&data, None).unwrap();
+        assert!(provider.mappings_for_game.find_struct("Actor").is_some());
+        let provider = UsmapProvider::from_read(&data[..], None).unwrap();
+        assert!(provider.mappings_for_game.find_struct("Actor").is_some());
+
+        // Oodle is only needed once the compression method, after the versioning flag, says so
+        let mut data = data;
+        data[7] = EUsmapCompressionMethod::Oodle as u8;
+        match UsmapProvider::from_bytes(&data, None) {
+            Err(UsmapParserError::OodleNotFound) => {}
+            other => panic!("unexpected {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn mappings_are_shareable() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<super::TypeMappings>();
+        assert_send_sync::<super::UsmapProvider>();
+        assert_send_sync::<crate::UEParse>();
+    }
+
+    #[test]
+    fn bad_data_is_an_error() {
+        // One name, no enums, one struct named by index 3 without properties
+        let mut data = vec![1, 0, 0, 0, 1, b'A', 0, 0, 0, 0, 1, 0, 0, 0];
+        data.extend([3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        match parse(&data) {
+            Err(UsmapParserError::NameIndexOutOfRange {
+                index: 3,
+                offset: 14,
+            }) => {}
+            other => panic!("unexpected {:?}", other.err()),
+        }
+
+        // A serializable property the data ends before
+        data[14] = 0;
+        data[24] = 1;
+        match parse(&data) {
+            Err(UsmapParserError::Truncated { offset: 26 }) => {}
+            other => panic!("unexpected {:?}", other.err()),
+        }
+
+        match parse(&[2, 0, 0, 0, 1, b'A']) {
+            Err(UsmapParserError::Truncated { offset: 6 }) => {}
+            other => panic!("unexpected {:?}", other.err()),
+        }
+
+        // A property typed as an array of arrays of arrays...
+        let mut nested = vec![1, 0, 0, 0, 1, b'A', 0, 0, 0, 0, 1, 0, 0, 0];
+        nested.extend([0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 1, 0, 0, 0, 1, 0, 0, 0, 0]);
+        nested.extend([8; 100_000]);
+        match parse(&nested) {
+            Err(UsmapParserError::TooDeeplyNested { offset }) => {
+                assert_eq!(offset, 33 + PropertyType::MAX_DEPTH as u64 + 1)
+            }
+            other => panic!("unexpected {:?}", other.err()),
+        }
+
+        let mut file = usmap(&[]);
+        file[2] = 5;
+        let result = UsmapParser::from_reader(&mut FileReader::new(Cursor::new(file)), None);
+        match result {
+            Err(UsmapParserError::UnsupportedVersion(5)) => {}
+            other => panic!("unexpected {:?}", other.err()),
+        }
+    }
+}
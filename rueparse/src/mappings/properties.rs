@@ -1,94 +1,129 @@
 use super::EPropertyType;
 use crate::objects::{UEnum, UStruct};
 use crate::{
-    mappings::TypeMappings,
+    mappings::UsmapParserError,
     readers::{FUsmapReader, Reader},
 };
-use once_cell::sync::OnceCell;
-use std::cell::RefCell;
 use std::collections::HashMap;
-use std::rc::Rc;
+use std::fmt;
+use std::iter;
+use std::sync::Arc;
 
+/// A struct or class layout.
 #[derive(Debug, Clone)]
 pub struct Struct {
-    pub context: Option<Rc<RefCell<TypeMappings>>>,
     pub name: String,
     pub super_type: Option<String>,
+    /// Set from `super_type` by `TypeMappings::new` once every struct is loaded.
+    pub super_struct: Option<Arc<Struct>>,
+    /// Own properties by index, one entry per static array element.
     pub properties: HashMap<i32, PropertyInfo>,
+    /// Own property count, including the ones that are not serialized.
     pub property_count: i32,
-    pub super_struct: OnceCell<Option<Box<Struct>>>,
 }
 
 impl Struct {
-    pub fn new(
-        context: Option<Rc<RefCell<TypeMappings>>>,
-        name: String,
-        property_count: i32,
-    ) -> Self {
+    pub fn new(name: String, property_count: i32) -> Self {
         Struct {
-            context,
             name,
             super_type: None,
+            super_struct: None,
             properties: HashMap::new(),
             property_count,
-            super_struct: OnceCell::new(),
         }
     }
     pub fn new_with_super(
-        context: Option<Rc<RefCell<TypeMappings>>>,
         name: String,
         super_type: Option<String>,
         properties: HashMap<i32, PropertyInfo>,
         property_count: i32,
     ) -> Self {
         Struct {
-            context,
             name,
             super_type,
+            super_struct: None,
             properties,
             property_count,
-            super_struct: OnceCell::new(),
         }
     }
 
-    pub fn init_super(&mut self) {
-        self.super_struct
-            .get_or_init(|| match (&self.context, &self.super_type) {
-                (Some(ctx), Some(super_name)) => ctx
-                    .borrow_mut()
-                    .types
-                    .borrow_mut()
-                    .get(super_name)
-                    .map(|s| s.clone()),
-                _ => None,
-            });
-        self.context = None; // To prevent infinity loop
+    /// This struct followed by its super structs, nearest first.
+    pub fn chain(&self) -> impl Iterator<Item = &Struct> {
+        iter::successors(Some(self), |s| s.super_struct.as_deref())
+    }
+
+    /// Property count including every super struct.
+    pub fn total_property_count(&self) -> i32 {
+        self.chain().map(|s| s.property_count).sum()
+    }
+
+    /// Every property including inherited ones, with its absolute index. Like the engine's
+    /// field iterator, own properties come first and each super struct's properties start
+    /// after the property counts of the structs before it.
+    pub fn all_properties(&self) -> impl Iterator<Item = (i32, &PropertyInfo)> {
+        self.chain()
+            .scan(0, |offset, s| {
+                let start = *offset;
+                *offset += s.property_count;
+                Some((start, s))
+            })
+            .flat_map(|(start, s)| {
+                let mut properties: Vec<_> = s.properties.iter().collect();
+                properties.sort_by_key(|(i, _)| **i);
+                properties.into_iter().map(move |(i, p)| (start + i, p))
+            })
+    }
+
+    /// Property by absolute index, as used by unversioned serialization.
+    pub fn property_by_index(&self, index: i32) -> Option<&PropertyInfo> {
+        let mut index = index;
+        for s in self.chain() {
+            if index < 0 {
+                return None;
+            }
+            if index < s.property_count {
+                return s.properties.get(&index);
+            }
+            index -= s.property_count;
+        }
+        None
+    }
+
+    /// Own or inherited property by name, ignoring ASCII case like `FName`. For static arrays
+    /// this is the first element.
+    pub fn find_property(&self, name: &str) -> Option<&PropertyInfo> {
+        self.chain().find_map(|s| {
+            s.properties
+                .values()
+                .find(|p| p.index == 0 && p.name.eq_ignore_ascii_case(name))
+        })
     }
 
     pub fn parse(
-        context: Option<Rc<RefCell<TypeMappings>>>,
         reader: &mut FUsmapReader,
         name_lut: &Vec<String>,
-    ) -> Self {
-        let name = reader.read_name(name_lut);
-        let super_type = Some(reader.read_name(name_lut));
+    ) -> Result<Self, UsmapParserError> {
+        let name = reader.read_name(name_lut)?;
+        let super_type = Some(reader.read_name(name_lut)?);
 
-        let property_count: u16 = reader.read_u16().unwrap();
-        let serializable_property_count: u16 = reader.read_u16().unwrap();
+        let property_count: u16 = reader.checked(|r| r.read_u16())?;
+        let serializable_property_count: u16 = reader.checked(|r| r.read_u16())?;
 
         let mut properties = HashMap::new();
         for _ in 0..serializable_property_count {
-            let prop_info: PropertyInfo = PropertyInfo::parse(reader, &name_lut);
+            let prop_info: PropertyInfo = PropertyInfo::parse(reader, name_lut)?;
             for i in 0..prop_info.array_size.unwrap_or(0) {
                 let mut clone: PropertyInfo = prop_info.clone();
                 clone.index = i as i32;
                 properties.insert(prop_info.index + i as i32, clone);
             }
         }
-        let mut new_super =
-            Struct::new_with_super(context, name, super_type, properties, property_count as i32);
-        new_super.init_super();
-        new_super
+        Ok(Struct::new_with_super(
+            name,
+            super_type,
+            properties,
+            property_count as i32,
+        ))
     }
 }
 
@@ -114,12 +149,20 @@ impl PropertyInfo {
             mapping_type,
         }
     }
-    pub fn parse(reader: &mut FUsmapReader, name_lut: &Vec<String>) -> Self {
-        let index: u16 = reader.read_u16().unwrap();
-        let arraydim: u8 = reader.read_u8().unwrap();
-        let name: String = reader.read_name(&name_lut);
-        let p_type: PropertyType = PropertyType::parse(reader, &name_lut);
-        PropertyInfo::new(index as i32, name, p_type, Some(arraydim))
+    pub fn parse(
+        reader: &mut FUsmapReader,
+        name_lut: &Vec<String>,
+    ) -> Result<Self, UsmapParserError> {
+        let index: u16 = reader.checked(|r| r.read_u16())?;
+        let arraydim: u8 = reader.checked(|r| r.read_u8())?;
+        let name: String = reader.read_name(name_lut)?;
+        let p_type: PropertyType = PropertyType::parse(reader, name_lut)?;
+        Ok(PropertyInfo::new(
+            index as i32,
+            name,
+            p_type,
+            Some(arraydim),
+        ))
     }
 }
 
@@ -158,8 +201,33 @@ impl PropertyType {
             f_enum: None,
         }
     }
-    pub fn parse(reader: &mut FUsmapReader, name_lut: &Vec<String>) -> Self {
-        let type_enum = EPropertyType::from_u8(reader.read_u8().unwrap());
+    /// Containers nest deeper than this only in crafted files.
+    pub const MAX_DEPTH: u32 = 32;
+
+    pub fn parse(
+        reader: &mut FUsmapReader,
+        name_lut: &Vec<String>,
+    ) -> Result<Self, UsmapParserError> {
+        Self::parse_nested(reader, name_lut, 0)
+    }
+
+    fn parse_nested(
+        reader: &mut FUsmapReader,
+        name_lut: &Vec<String>,
+        depth: u32,
+    ) -> Result<Self, UsmapParserError> {
+        if depth > Self::MAX_DEPTH {
+            let offset = reader.checked(|r| r.position())?;
+            return Err(UsmapParserError::TooDeeplyNested { offset });
+        }
+        let inner = |reader: &mut FUsmapReader| -> Result<Option<Box<Self>>, UsmapParserError> {
+            Ok(Some(Box::new(Self::parse_nested(
+                reader,
+                name_lut,
+                depth + 1,
+            )?)))
+        };
+        let type_enum = EPropertyType::from_u8(reader.checked(|r| r.read_u8())?);
         let f_type: String = format!("{:?}", type_enum);
         let mut struct_type: Option<String> = None;
         let mut inner_type: Option<Box<PropertyType>> = None;
@@ -169,24 +237,24 @@ impl PropertyType {
 
         match type_enum {
             EPropertyType::EnumProperty => {
-                inner_type = Some(Box::new(PropertyType::parse(reader, &name_lut)));
-                enum_name = Some(reader.read_name(&name_lut));
+                inner_type = inner(reader)?;
+                enum_name = Some(reader.read_name(name_lut)?);
             }
             EPropertyType::StructProperty => {
-                struct_type = Some(reader.read_name(&name_lut));
+                struct_type = Some(reader.read_name(name_lut)?);
             }
             EPropertyType::SetProperty
             | EPropertyType::ArrayProperty
             | EPropertyType::OptionalProperty => {
-                inner_type = Some(Box::new(PropertyType::parse(reader, &name_lut)));
+                inner_type = inner(reader)?;
             }
             EPropertyType::MapProperty => {
-                inner_type = Some(Box::new(PropertyType::parse(reader, &name_lut)));
-                value_type = Some(Box::new(PropertyType::parse(reader, &name_lut)));
+                inner_type = inner(reader)?;
+                value_type = inner(reader)?;
             }
             _ => {}
         }
-        PropertyType::new(
+        Ok(PropertyType::new(
             f_type,
             struct_type,
             inner_type,
@@ -194,6 +262,30 @@ impl PropertyType {
             enum_name,
             is_enum_as_byte,
             None,
-        )
+        ))
+    }
+}
+
+/// Writes the type tree, for example `MapProperty<NameProperty, ArrayProperty<StructProperty<Vector>>>`.
+impl fmt::Display for PropertyType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.f_type)?;
+        let mut arguments: Vec<String> = Vec::new();
+        if let Some(inner) = &self.inner_type {
+            arguments.push(inner.to_string());
+        }
+        if let Some(value) = &self.value_type {
+            arguments.push(value.to_string());
+        }
+        if let Some(name) = &self.struct_type {
+            arguments.push(name.clone());
+        }
+        if let Some(name) = &self.enum_name {
+            arguments.push(name.clone());
+        }
+        if !arguments.is_empty() {
+            write!(f, "<{}>", arguments.join(", "))?;
+        }
+        Ok(())
     }
 }
@@ -1,50 +1,34 @@
-use std::io::{self, Read, Seek};
-
-use crate::models::FGuid;
-use crate::readers::Reader;
+pub use crate::versions::FPackageFileVersion;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EUsmapVersion {
     Initial,
+    /// Optional package version, custom versions and netcl
     PackageVersioning,
+    /// u16 name lengths
     LongFName,
+    /// u16 enum entry counts
     LargeEnums,
+    /// An i64 value before every enum entry name
+    ExplicitEnumValues,
 }
 
 impl EUsmapVersion {
-    pub const LATEST: EUsmapVersion = EUsmapVersion::LargeEnums;
-}
-
-impl From<u8> for EUsmapVersion {
-    fn from(orig: u8) -> Self {
-        match orig {
-            0 => return EUsmapVersion::Initial,
-            1 => return EUsmapVersion::PackageVersioning,
-            2 => return EUsmapVersion::LongFName,
-            3 => return EUsmapVersion::LargeEnums,
-            _ => return EUsmapVersion::LATEST,
-        };
-    }
+    pub const LATEST: EUsmapVersion = EUsmapVersion::ExplicitEnumValues;
 }
 
-pub struct FPackageFileVersion {
-    pub file_version_ue4: i32,
-    pub file_version_ue5: i32,
-}
+/// Fails with the version byte when it is newer than `LATEST`.
+impl TryFrom<u8> for EUsmapVersion {
+    type Error = u8;
 
-impl FPackageFileVersion {
-    pub fn from_reader(reader: &mut dyn Reader) -> io::Result<Self> {
-        let file_version_ue4 = reader.read_i32()?;
-        let file_version_ue5 = reader.read_i32()?;
-        Ok(Self {
-            file_version_ue4,
-            file_version_ue5,
-        })
-    }
-    pub fn default() -> Self {
-        Self {
-            file_version_ue4: 0,
-            file_version_ue5: 0,
+    fn try_from(orig: u8) -> Result<Self, u8> {
+        match orig {
+            0 => Ok(EUsmapVersion::Initial),
+            1 => Ok(EUsmapVersion::PackageVersioning),
+            2 => Ok(EUsmapVersion::LongFName),
+            3 => Ok(EUsmapVersion::LargeEnums),
+            4 => Ok(EUsmapVersion::ExplicitEnumValues),
+            _ => Err(orig),
         }
     }
 }
@@ -0,0 +1,525 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use oodle::Oodle;
+
+use super::{
+    EPropertyType, EUsmapCompressionMethod, EUsmapVersion, FPackageFileVersion, PropertyType,
+    Struct, TypeMappings,
+};
+use crate::compression::{CompressionError, CompressionLevel, CompressionMethod, compress};
+use crate::objects::core::serialization::FCustomVersionContainer;
+use crate::writers::{FileWriter, Writer};
+
+#[derive(Debug)]
+pub enum UsmapWriterError {
+    InvalidCompressionMethod,
+    /// Versioning is only stored from `EUsmapVersion::PackageVersioning` on.
+    VersioningNotSupported,
+    /// A name, enum or struct with more entries than the version's length prefix can hold.
+    TooLong(String),
+    /// A container, enum or struct property without the type it refers to.
+    IncompleteType(String),
+    /// An enum whose values are not `0..n`, which needs `EUsmapVersion::ExplicitEnumValues`.
+    ExplicitEnumValuesNotSupported(String),
+    CompressionError(CompressionError),
+    WriteError(io::Error),
+}
+
+impl From<io::Error> for UsmapWriterError {
+    fn from(error: io::Error) -> Self {
+        UsmapWriterError::WriteError(error)
+    }
+}
+
+/// The optional block after the version byte.
+#[derive(Debug, Clone)]
+pub struct UsmapVersioning {
+    pub package_version: FPackageFileVersion,
+    pub custom_versions: FCustomVersionContainer,
+    pub netcl: u32,
+}
+
+/// Serializes `TypeMappings` in the layout `UsmapParser` reads.
+///
+/// Structs and enums are written sorted by name, so the same mappings always give the same file.
+#[derive(Debug, Clone)]
+pub struct UsmapWriter {
+    pub version: EUsmapVersion,
+    pub compression_method: EUsmapCompressionMethod,
+    pub compression_level: CompressionLevel,
+    pub versioning: Option<UsmapVersioning>,
+}
+
+impl UsmapWriter {
+    pub fn new(version: EUsmapVersion, compression_method: EUsmapCompressionMethod) -> Self {
+        Self {
+            version,
+            compression_method,
+            compression_level: CompressionLevel::Default,
+            versioning: None,
+        }
+    }
+
+    pub fn with_versioning(mut self, versioning: UsmapVersioning) -> Self {
+        self.versioning = Some(versioning);
+        self
+    }
+
+    pub fn with_level(mut self, level: CompressionLevel) -> Self {
+        self.compression_level = level;
+        self
+    }
+
+    /// `oo` is only needed for `EUsmapCompressionMethod::Oodle`.
+    pub fn write(
+        &self,
+        mappings: &TypeMappings,
+        writer: &mut dyn Writer,
+        oo: Option<&Oodle>,
+    ) -> Result<(), UsmapWriterError> {
+        if self.compression_method == EUsmapCompressionMethod::Unknown {
+            return Err(UsmapWriterError::InvalidCompressionMethod);
+        }
+        let has_versioning = self.version as u8 >= EUsmapVersion::PackageVersioning as u8;
+        if self.versioning.is_some() && !has_versioning {
+            return Err(UsmapWriterError::VersioningNotSupported);
+        }
+
+        let data = BodyWriter::new(self.version).write(mappings)?;
+        let compressed = match compress(
+            CompressionMethod::from(self.compression_method),
+            &data,
+            self.compression_level,
+            oo,
+        ) {
+            Ok(c) => c,
+            Err(e) => return Err(UsmapWriterError::CompressionError(e)),
+        };
+
+        writer.write_u16(0x30C4)?;
+        writer.write_u8(self.version as u8)?;
+        if has_versioning {
+            writer.write_bool(self.versioning.is_some())?;
+            if let Some(versioning) = &self.versioning {
+                versioning.package_version.to_writer(writer)?;
+                versioning.custom_versions.to_writer(writer)?;
+                writer.write_u32(versioning.netcl)?;
+            }
+        }
+        writer.write_u8(self.compression_method as u8)?;
+        writer.write_u32(size(compressed.len(), "compressed data")?)?;
+        writer.write_u32(size(data.len(), "data")?)?;
+        writer.write_all(&compressed)?;
+        Ok(())
+    }
+
+    pub fn save(
+        &self,
+        mappings: &TypeMappings,
+        path: &str,
+        oo: Option<&Oodle>,
+    ) -> Result<(), UsmapWriterError> {
+        let file = File::create(path)?;
+        let mut writer = FileWriter::new(BufWriter::new(file));
+        self.write(mappings, &mut writer, oo)?;
+        writer.into_inner().flush()?;
+        Ok(())
+    }
+
+    pub fn to_bytes(
+        &self,
+        mappings: &TypeMappings,
+        oo: Option<&Oodle>,
+    ) -> Result<Vec<u8>, UsmapWriterError> {
+        let mut writer = FileWriter::new(Vec::new());
+        self.write(mappings, &mut writer, oo)?;
+        Ok(writer.into_inner())
+    }
+}
+
+fn size(length: usize, what: &str) -> Result<u32, UsmapWriterError> {
+    match u32::try_from(length) {
+        Ok(l) => Ok(l),
+        Err(_) => Err(UsmapWriterError::TooLong(what.to_string())),
+    }
+}
+
+/// Writes enums and structs while collecting the name table, which goes in front of them.
+struct BodyWriter {
+    version: EUsmapVersion,
+    names: Vec<String>,
+    indices: HashMap<String, i32>,
+    out: FileWriter<Vec<u8>>,
+}
+
+impl BodyWriter {
+    fn new(version: EUsmapVersion) -> Self {
+        Self {
+            version,
+            names: Vec::new(),
+            indices: HashMap::new(),
+            out: FileWriter::new(Vec::new()),
+        }
+    }
+
+    fn write(mut self, mappings: &TypeMappings) -> Result<Vec<u8>, UsmapWriterError> {
+        let enums = &mappings.enums;
+        let mut enum_names: Vec<&String> = enums.keys().collect();
+        enum_names.sort();
+        self.out.write_u32(size(enum_names.len(), "enums")?)?;
+        for name in enum_names {
+            let values = &enums[name];
+            let mut keys: Vec<i64> = values.keys().copied().collect();
+            keys.sort();
+            let explicit_values = self.version as u8 >= EUsmapVersion::ExplicitEnumValues as u8;
+            if !explicit_values && keys.iter().enumerate().any(|(i, k)| i as i64 != *k) {
+                return Err(UsmapWriterError::ExplicitEnumValuesNotSupported(
+                    name.clone(),
+                ));
+            }
+            self.write_name(name)?;
+            self.write_enum_length(keys.len(), name)?;
+            for key in keys {
+                if explicit_values {
+                    self.out.write_i64(key)?;
+                }
+                self.write_name(&values[&key])?;
+            }
+        }
+
+        let types = &mappings.types;
+        let mut struct_names: Vec<&String> = types.keys().collect();
+        struct_names.sort();
+        self.out.write_u32(size(struct_names.len(), "structs")?)?;
+        for name in struct_names {
+            self.write_struct(&types[name])?;
+        }
+
+        let mut data = FileWriter::new(Vec::new());
+        data.write_u32(size(self.names.len(), "names")?)?;
+        for name in &self.names {
+            if self.version as u8 >= EUsmapVersion::LongFName as u8 {
+                match u16::try_from(name.len()) {
+                    Ok(l) => data.write_u16(l)?,
+                    Err(_) => return Err(UsmapWriterError::TooLong(name.clone())),
+                }
+            } else {
+                match u8::try_from(name.len()) {
+                    Ok(l) => data.write_u8(l)?,
+                    Err(_) => return Err(UsmapWriterError::TooLong(name.clone())),
+                }
+            }
+            data.write_all(name.as_bytes())?;
+        }
+        data.write_all(&self.out.into_inner())?;
+        Ok(data.into_inner())
+    }
+
+    fn write_enum_length(&mut self, length: usize, owner: &str) -> Result<(), UsmapWriterError> {
+        if self.version as u8 >= EUsmapVersion::LargeEnums as u8 {
+            match u16::try_from(length) {
+                Ok(l) => self.out.write_u16(l)?,
+                Err(_) => return Err(UsmapWriterError::TooLong(owner.to_string())),
+            }
+        } else {
+            match u8::try_from(length) {
+                Ok(l) => self.out.write_u8(l)?,
+                Err(_) => return Err(UsmapWriterError::TooLong(owner.to_string())),
+            }
+        }
+        Ok(())
+    }
+
+    /// Empty names are stored as -1.
+    fn write_name(&mut self, name: &str) -> Result<(), UsmapWriterError> {
+        if name.is_empty() {
+            self.out.write_i32(-1)?;
+            return Ok(());
+        }
+        let index = match self.indices.get(name) {
+            Some(i) => *i,
+            None => {
+                let i = self.names.len() as i32;
+                self.names.push(name.to_string());
+                self.indices.insert(name.to_string(), i);
+                i
+            }
+        };
+        self.out.write_i32(index)?;
+        Ok(())
+    }
+
+    fn write_struct(&mut self, s: &Struct) -> Result<(), UsmapWriterError> {
+        // Static arrays are stored once per element, the first one has index 0
+        let mut properties: Vec<_> = s.properties.iter().filter(|(_, p)| p.index == 0).collect();
+        properties.sort_by_key(|(k, _)| **k);
+
+        self.write_name(&s.name)?;
+        self.write_name(s.super_type.as_deref().unwrap_or(""))?;
+        let property_count = match u16::try_from(s.property_count) {
+            Ok(c) => c,
+            Err(_) => return Err(UsmapWriterError::TooLong(s.name.clone())),
+        };
+        let serializable_count = match u16::try_from(properties.len()) {
+            Ok(c) => c,
+            Err(_) => return Err(UsmapWriterError::TooLong(s.name.clone())),
+        };
+        self.out.write_u16(property_count)?;
+        self.out.write_u16(serializable_count)?;
+        for (index, property) in properties {
+            match u16::try_from(*index) {
+                Ok(i) => self.out.write_u16(i)?,
+                Err(_) => return Err(UsmapWriterError::TooLong(s.name.clone())),
+            }
+            self.out.write_u8(property.array_size.unwrap_or(1))?;
+            self.write_name(&property.name)?;
+            self.write_type(&property.mapping_type)?;
+        }
+        Ok(())
+    }
+
+    fn write_type(&mut self, property_type: &PropertyType) -> Result<(), UsmapWriterError> {
+        let type_enum = EPropertyType::from_name(&property_type.f_type);
+        self.out.write_u8(type_enum.clone() as u8)?;
+        let incomplete = || UsmapWriterError::IncompleteType(property_type.f_type.clone());
+        match type_enum {
+            EPropertyType::EnumProperty => {
+                match &property_type.inner_type {
+                    Some(inner) => self.write_type(inner)?,
+                    None => return Err(incomplete()),
+                }
+                match &property_type.enum_name {
+                    Some(name) => self.write_name(name)?,
+                    None => return Err(incomplete()),
+                }
+            }
+            EPropertyType::StructProperty => match &property_type.struct_type {
+                Some(name) => self.write_name(name)?,
+                None => return Err(incomplete()),
+            },
+            EPropertyType::SetProperty
+            | EPropertyType::ArrayProperty
+            | EPropertyType::OptionalProperty => match &property_type.inner_type {
+                Some(inner) => self.write_type(inner)?,
+                None => return Err(incomplete()),
+            },
+            EPropertyType::MapProperty => {
+                match (&property_type.inner_type, &property_type.value_type) {
+                    (Some(key), Some(value)) => {
+                        self.write_type(key)?;
+                        self.write_type(value)?;
+                    }
+                    _ => return Err(incomplete()),
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{UsmapVersioning, UsmapWriter, UsmapWriterError};
+    use crate::compression::CompressionLevel;
+    use crate::mappings::{
+        EUsmapCompressionMethod, EUsmapVersion, FPackageFileVersion, PropertyInfo, PropertyType,
+        Struct, TypeMappings, UsmapParser,
+    };
+    use crate::models::FGuid;
+    use crate::objects::core::serialization::{FCustomVersion, FCustomVersionContainer};
+    use crate::readers::FileReader;
+    use std::collections::HashMap;
+    use std::io::Cursor;
+
+    fn simple(f_type: &str) -> PropertyType {
+        PropertyType::new(f_type.to_string(), None, None, None, None, None, None)
+    }
+
+    fn container(f_type: &str, inner: PropertyType, value: Option<PropertyType>) -> PropertyType {
+        PropertyType::new(
+            f_type.to_string(),
+            None,
+            Some(Box::new(inner)),
+            value.map(Box::new),
+            None,
+            None,
+            None,
+        )
+    }
+
+    fn sample_parts() -> (
+        HashMap<String, Struct>,
+        HashMap<String, HashMap<i64, String>>,
+    ) {
+        let mut color = simple("EnumProperty");
+        color.inner_type = Some(Box::new(simple("ByteProperty")));
+        color.enum_name = Some("EColor".to_string());
+        let mut location = simple("StructProperty");
+        location.struct_type = Some("Vector".to_string());
+        let scores = container(
+            "MapProperty",
+            simple("NameProperty"),
+            Some(container("ArrayProperty", simple("FloatProperty"), None)),
+        );
+
+        let mut properties = HashMap::new();
+        properties.insert(0, PropertyInfo::new(0, "Color".into(), color, Some(1)));
+        properties.insert(
+            1,
+            PropertyInfo::new(0, "Location".into(), location, Some(1)),
+        );
+        for i in 0..3 {
+            let info = PropertyInfo::new(i, "Slots".into(), simple("IntProperty"), Some(3));
+            properties.insert(2 + i, info);
+        }
+        properties.insert(5, PropertyInfo::new(0, "Scores".into(), scores, Some(1)));
+
+        let mut types = HashMap::new();
+        let actor = Struct::new_with_super("Actor".into(), Some(String::new()), HashMap::new(), 0);
+        let pawn = Struct::new_with_super("Pawn".into(), Some("Actor".into()), properties, 7);
+        types.insert("Actor".to_string(), actor);
+        types.insert("Pawn".to_string(), pawn);
+
+        let mut enums = HashMap::new();
+        let values = ["Red", "Green", "Blue"].iter().enumerate();
+        enums.insert(
+            "EColor".to_string(),
+            values.map(|(i, v)| (i as i64, v.to_string())).collect(),
+        );
+        (types, enums)
+    }
+
+    fn sample() -> TypeMappings {
+        let (types, enums) = sample_parts();
+        TypeMappings::new(types, enums)
+    }
+
+    fn parse(data: Vec<u8>) -> UsmapParser {
+        UsmapParser::from_reader(&mut FileReader::new(Cursor::new(data)), None).unwrap()
+    }
+
+    #[test]
+    fn round_trips_every_version_and_compression() {
+        let mappings = sample();
+        for version in [
+            EUsmapVersion::Initial,
+            EUsmapVersion::PackageVersioning,
+            EUsmapVersion::LongFName,
+            EUsmapVersion::LargeEnums,
+            EUsmapVersion::ExplicitEnumValues,
+        ] {
+            for compression in [
+                EUsmapCompressionMethod::None,
+                EUsmapCompressionMethod::Brotli,
+                EUsmapCompressionMethod::ZStandart,
+            ] {
+                let writer =
+                    UsmapWriter::new(version, compression).with_level(CompressionLevel::Fast);
+                let data = writer.to_bytes(&mappings, None).unwrap();
+                let parsed = parse(data.clone());
+                assert_eq!(parsed.version, version);
+                assert_eq!(parsed.compression_method, compression);
+                // The writer covers every field the parser reads, so equal output means equal mappings
+                assert_eq!(writer.to_bytes(&parsed.mappings, None).unwrap(), data);
+
+                let pawn = &parsed.mappings.types["Pawn"];
+                assert_eq!(pawn.super_type.as_deref(), Some("Actor"));
+                assert_eq!(pawn.property_count, 7);
+                assert_eq!(pawn.properties.len(), 6);
+                assert_eq!(pawn.properties[&4].name, "Slots");
+                assert_eq!(pawn.properties[&4].index, 2);
+                let scores = &pawn.properties[&5].mapping_type;
+                assert_eq!(scores.value_type.as_ref().unwrap().f_type, "ArrayProperty");
+                assert_eq!(parsed.mappings.enums["EColor"][&2], "Blue");
+            }
+        }
+    }
+
+    #[test]
+    fn enum_layout_follows_version() {
+        let (types, mut enums) = sample_parts();
+        let mut flags: HashMap<i64, String> = HashMap::new();
+        flags.insert(-1, "None".to_string());
+        flags.insert(1 << 40, "Large".to_string());
+        enums.insert("EFlags".to_string(), flags);
+        let mappings = TypeMappings::new(types, enums);
+
+        let writer = UsmapWriter::new(
+            EUsmapVersion::ExplicitEnumValues,
+            EUsmapCompressionMethod::None,
+        );
+        let parsed = parse(writer.to_bytes(&mappings, None).unwrap());
+        let enums = &parsed.mappings.enums;
+        assert_eq!(enums["EFlags"][&-1], "None");
+        assert_eq!(enums["EFlags"][&(1 << 40)], "Large");
+
+        let writer = UsmapWriter::new(EUsmapVersion::LargeEnums, EUsmapCompressionMethod::None);
+        assert!(matches!(
+            writer.to_bytes(&mappings, None),
+            Err(UsmapWriterError::ExplicitEnumValuesNotSupported(_))
+        ));
+
+        // Entry counts only widen to u16 with LargeEnums
+        let (types, mut enums) = sample_parts();
+        let values = (0..300).map(|i| (i, format!("V{}", i))).collect();
+        enums.insert("EWide".to_string(), values);
+        let mappings = TypeMappings::new(types, enums);
+        let writer = UsmapWriter::new(EUsmapVersion::LongFName, EUsmapCompressionMethod::None);
+        assert!(matches!(
+            writer.to_bytes(&mappings, None),
+            Err(UsmapWriterError::TooLong(_))
+        ));
+        let writer = UsmapWriter::new(EUsmapVersion::LargeEnums, EUsmapCompressionMethod::None);
+        let parsed = parse(writer.to_bytes(&mappings, None).unwrap());
+        assert_eq!(parsed.mappings.enums["EWide"][&299], "V299");
+    }
+
+    #[test]
+    fn writes_versioning_block() {
+        let versioning = UsmapVersioning {
+            package_version: FPackageFileVersion {
+                file_version_ue4: 522,
+                file_version_ue5: 1012,
+            },
+            custom_versions: FCustomVersionContainer::from_versions(vec![FCustomVersion::new(
+                FGuid::new(1, 2, 3, 4),
+                17,
+            )]),
+            netcl: 123456,
+        };
+        let writer = UsmapWriter::new(EUsmapVersion::LargeEnums, EUsmapCompressionMethod::None)
+            .with_versioning(versioning.clone());
+        let parsed = parse(writer.to_bytes(&sample(), None).unwrap());
+        assert_eq!(parsed.package_version, versioning.package_version);
+        assert_eq!(parsed.custom_versions, versioning.custom_versions);
+        assert_eq!(parsed.netcl, 123456);
+
+        let writer = UsmapWriter::new(EUsmapVersion::Initial, EUsmapCompressionMethod::None)
+            .with_versioning(versioning);
+        assert!(writer.to_bytes(&sample(), None).is_err());
+    }
+
+    // Set OODLE_LIB_PATH to run against the native library
+    #[test]
+    fn oodle_round_trip() {
+        let path = match std::env::var("OODLE_LIB_PATH") {
+            Ok(p) => p,
+            Err(_) => return,
+        };
+        let oodle = oodle::Oodle::load(&path).unwrap();
+        let writer = UsmapWriter::new(EUsmapVersion::LATEST, EUsmapCompressionMethod::Oodle);
+        let data = writer.to_bytes(&sample(), Some(&oodle)).unwrap();
+        let parsed = UsmapParser::from_reader(
+            &mut FileReader::new(Cursor::new(data.clone())),
+            Some(&oodle),
+        )
+        .unwrap();
+        assert_eq!(
+            writer.to_bytes(&parsed.mappings, Some(&oodle)).unwrap(),
+            data
+        );
+    }
+}
@@ -1,6 +1,10 @@
+use std::io;
 use std::string::FromUtf8Error;
 
+use aes::Aes256;
+use aes::cipher::{BlockDecrypt, KeyInit, generic_array::GenericArray};
 use hex;
+#[derive(Debug, Clone)]
 pub struct FAesKey {
     key: Vec<u8>,
     pub key_string: String,
@@ -35,4 +39,31 @@ impl FAesKey {
             key_string: format!("0x{}", key_string),
         })
     }
+
+    pub fn key(&self) -> &[u8] {
+        &self.key
+    }
+
+    /// Decrypts `data` in place with AES-256 in ECB mode, as used for pak and IoStore indices.
+    pub fn decrypt(&self, data: &mut [u8]) -> io::Result<()> {
+        if !data.len().is_multiple_of(16) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Encrypted data is not aligned to the AES block size",
+            ));
+        }
+        let cipher = match Aes256::new_from_slice(&self.key) {
+            Ok(c) => c,
+            Err(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "AES key must be 32 bytes long",
+                ));
+            }
+        };
+        for block in data.chunks_exact_mut(16) {
+            cipher.decrypt_block(GenericArray::from_mut_slice(block));
+        }
+        Ok(())
+    }
 }
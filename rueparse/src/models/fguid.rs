@@ -1,8 +1,9 @@
-use crate::readers::Reader;
+use crate::readers::UeRead;
+use crate::writers::Writer;
 use std::fmt;
 use std::io;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, UeRead)]
 pub struct FGuid {
     pub a: u32,
     pub b: u32,
@@ -15,6 +16,10 @@ impl FGuid {
         if hex.len() != 32 {
             return Err("Hex string must be 32 characters long");
         }
+        // Also keeps the byte offsets below on character boundaries
+        if !hex.is_ascii() {
+            return Err("Invalid hex format");
+        }
         let a = u32::from_str_radix(&hex[0..8], 16).map_err(|_| "Invalid hex format")?;
         let b = u32::from_str_radix(&hex[8..16], 16).map_err(|_| "Invalid hex format")?;
         let c = u32::from_str_radix(&hex[16..24], 16).map_err(|_| "Invalid hex format")?;
@@ -39,13 +44,11 @@ impl FGuid {
             d,
         })
     }
-    pub fn from_reader(reader: &mut dyn Reader) -> io::Result<Self> {
-        Ok(Self {
-            a: reader.read_u32()?,
-            b: reader.read_u32()?,
-            c: reader.read_u32()?,
-            d: reader.read_u32()?,
-        })
+    pub fn to_writer(&self, writer: &mut dyn Writer) -> io::Result<()> {
+        writer.write_u32(self.a)?;
+        writer.write_u32(self.b)?;
+        writer.write_u32(self.c)?;
+        writer.write_u32(self.d)
     }
     pub fn new(a: u32, b: u32, c: u32, d: u32) -> Self {
         Self { a, b, c, d }
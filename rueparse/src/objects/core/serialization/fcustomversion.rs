@@ -1,9 +1,10 @@
 use std::io;
 
 use crate::models::FGuid;
-use crate::readers::Reader;
+use crate::readers::UeRead;
+use crate::writers::Writer;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, UeRead)]
 pub struct FCustomVersion {
     key: FGuid,
     version: i32,
@@ -13,11 +14,17 @@ impl FCustomVersion {
         Self { key, version }
     }
 
-    pub fn from_reader(reader: &mut dyn Reader) -> io::Result<Self> {
-        Ok(Self {
-            key: FGuid::from_reader(reader)?,
-            version: reader.read_i32()?,
-        })
+    pub fn to_writer(&self, writer: &mut dyn Writer) -> io::Result<()> {
+        self.key.to_writer(writer)?;
+        writer.write_i32(self.version)
+    }
+
+    pub fn key(&self) -> &FGuid {
+        &self.key
+    }
+
+    pub fn version(&self) -> i32 {
+        self.version
     }
 }
 
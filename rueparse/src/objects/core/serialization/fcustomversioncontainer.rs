@@ -1,9 +1,11 @@
 use std::io;
 
 use super::{ECustomVersionSerializationFormat, FCustomVersion};
+use crate::models::FGuid;
 use crate::readers::Reader;
+use crate::writers::Writer;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FCustomVersionContainer {
     pub versions: Vec<FCustomVersion>,
 }
@@ -16,6 +18,12 @@ impl FCustomVersionContainer {
     pub fn from_versions(versions: Vec<FCustomVersion>) -> Self {
         Self { versions }
     }
+    pub fn get_version(&self, key: &FGuid) -> Option<i32> {
+        self.versions
+            .iter()
+            .find(|v| v.key() == key)
+            .map(|v| v.version())
+    }
     pub fn new(
         reader: &mut dyn Reader,
         format: Option<ECustomVersionSerializationFormat>,
@@ -23,8 +31,10 @@ impl FCustomVersionContainer {
         let f = format.unwrap_or(ECustomVersionSerializationFormat::LATEST);
         match f {
             ECustomVersionSerializationFormat::Optimized => {
-                let length = reader.read_i32()?;
-                let mut versions: Vec<FCustomVersion> = Vec::with_capacity(length as usize);
+                let length = reader.read_array_length()?;
+                // Each version is a 16 byte key and an i32
+                reader.check_allocation(length as u64, 20)?;
+                let mut versions: Vec<FCustomVersion> = Vec::with_capacity(length);
                 for _ in 0..length {
                     versions.push(match FCustomVersion::from_reader(reader) {
                         Ok(f) => f,
@@ -41,4 +51,12 @@ impl FCustomVersionContainer {
             }
         }
     }
+    /// Writes the container in the `Optimized` format read by `new`.
+    pub fn to_writer(&self, writer: &mut dyn Writer) -> io::Result<()> {
+        writer.write_array_length(self.versions.len())?;
+        for version in &self.versions {
+            version.to_writer(writer)?;
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,62 @@
+/// Byte order of the primitives in a reader. Desktop and modern console builds are little-endian,
+/// some older console builds store their data big-endian.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Endianness {
+    #[default]
+    Little,
+    Big,
+}
+
+impl Endianness {
+    pub fn u16(self, bytes: [u8; 2]) -> u16 {
+        match self {
+            Endianness::Little => u16::from_le_bytes(bytes),
+            Endianness::Big => u16::from_be_bytes(bytes),
+        }
+    }
+
+    pub fn u32(self, bytes: [u8; 4]) -> u32 {
+        match self {
+            Endianness::Little => u32::from_le_bytes(bytes),
+            Endianness::Big => u32::from_be_bytes(bytes),
+        }
+    }
+
+    pub fn i32(self, bytes: [u8; 4]) -> i32 {
+        self.u32(bytes) as i32
+    }
+
+    pub fn u64(self, bytes: [u8; 8]) -> u64 {
+        match self {
+            Endianness::Little => u64::from_le_bytes(bytes),
+            Endianness::Big => u64::from_be_bytes(bytes),
+        }
+    }
+
+    pub fn i64(self, bytes: [u8; 8]) -> i64 {
+        self.u64(bytes) as i64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Endianness;
+    use crate::readers::{FileReader, Reader};
+    use std::io::Cursor;
+
+    #[test]
+    fn primitives_follow_reader_byte_order() {
+        let data = vec![
+            0x00, 0x00, 0x00, 0x02, 0x00, 0x41, 0x00, 0x00, 0x3F, 0x80, 0x00, 0x00,
+        ];
+        let mut reader = FileReader::new(Cursor::new(data.clone()));
+        assert_eq!(reader.read_u32().unwrap(), 0x0200_0000);
+
+        let mut reader = FileReader::new(Cursor::new(data));
+        reader.set_endianness(Endianness::Big);
+        assert_eq!(reader.read_i32().unwrap(), 2);
+        assert_eq!(reader.read_u16().unwrap(), 0x41);
+        assert_eq!(reader.read_u16().unwrap(), 0);
+        assert_eq!(reader.read_f32().unwrap(), 1.0);
+    }
+}
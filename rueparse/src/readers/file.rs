@@ -1,15 +1,35 @@
 use crate::models::FGuid;
-use crate::readers::Reader;
-use byteorder::{LittleEndian, ReadBytesExt};
+use crate::readers::{Endianness, Reader, ReaderLimits, UeRead};
+use crate::writers::Writer;
+use byteorder::ReadBytesExt;
 use std::fmt;
 use std::io::{self, Read, Seek, SeekFrom};
 
 pub struct FileReader<R: Read + Seek> {
     inner: R,
+    len: Option<u64>,
+    limits: ReaderLimits,
+    endianness: Endianness,
 }
 impl<R: Read + Seek> FileReader<R> {
     pub fn new(inner: R) -> Self {
-        Self { inner }
+        Self::with_limits(inner, ReaderLimits::DEFAULT)
+    }
+    pub fn with_limits(inner: R, limits: ReaderLimits) -> Self {
+        Self {
+            inner,
+            len: None,
+            limits,
+            endianness: Endianness::Little,
+        }
+    }
+    pub fn set_endianness(&mut self, endianness: Endianness) {
+        self.endianness = endianness;
+    }
+    fn read_raw<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        let mut bytes = [0u8; N];
+        self.inner.read_exact(&mut bytes)?;
+        Ok(bytes)
     }
 }
 impl<R: Read + Seek> Reader for FileReader<R> {
@@ -18,23 +38,23 @@ impl<R: Read + Seek> Reader for FileReader<R> {
     }
 
     fn read_u16(&mut self) -> io::Result<u16> {
-        self.inner.read_u16::<LittleEndian>()
+        Ok(self.endianness.u16(self.read_raw()?))
     }
 
     fn read_i32(&mut self) -> io::Result<i32> {
-        self.inner.read_i32::<LittleEndian>()
+        Ok(self.endianness.i32(self.read_raw()?))
     }
 
     fn read_u32(&mut self) -> io::Result<u32> {
-        self.inner.read_u32::<LittleEndian>()
+        Ok(self.endianness.u32(self.read_raw()?))
     }
 
     fn read_u64(&mut self) -> io::Result<u64> {
-        self.inner.read_u64::<LittleEndian>()
+        Ok(self.endianness.u64(self.read_raw()?))
     }
 
     fn read_i64(&mut self) -> io::Result<i64> {
-        self.inner.read_i64::<LittleEndian>()
+        Ok(self.endianness.i64(self.read_raw()?))
     }
 
     fn read_exact(&mut self, o: &mut [u8]) -> io::Result<()> {
@@ -45,24 +65,32 @@ impl<R: Read + Seek> Reader for FileReader<R> {
         self.inner.seek(SeekFrom::Start(pos))
     }
 
-    fn read_bool(&mut self) -> io::Result<bool> {
-        match self.read_i32() {
-            Ok(u) => match u {
-                0 => return Ok(false),
-                1 => return Ok(true),
-                _ => {
-                    return Err(io::Error::new(
-                        io::ErrorKind::InvalidData,
-                        "Invalid TOC magic",
-                    ));
-                }
-            },
-            Err(e) => return Err(e),
+    fn position(&mut self) -> io::Result<u64> {
+        self.inner.stream_position()
+    }
+
+    // Seeking to the end drops the read buffer, so the length is only looked up once
+    fn len(&mut self) -> io::Result<u64> {
+        if let Some(len) = self.len {
+            return Ok(len);
         }
+        let position = self.inner.stream_position()?;
+        let len = self.inner.seek(SeekFrom::End(0))?;
+        self.inner.seek(SeekFrom::Start(position))?;
+        self.len = Some(len);
+        Ok(len)
+    }
+
+    fn limits(&self) -> ReaderLimits {
+        self.limits
+    }
+
+    fn endianness(&self) -> Endianness {
+        self.endianness
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, UeRead)]
 pub struct FIoContainerId(pub u64);
 
 impl fmt::Display for FIoContainerId {
@@ -79,8 +107,13 @@ pub enum EIoContainerFlags {
     Indexed = 1 << 3,
     OnDemand = 1 << 4,
 }
-#[derive(Debug)]
+const TOC_MAGIC: [u8; 16] = [
+    0x2D, 0x3D, 0x3D, 0x2D, 0x2D, 0x3D, 0x3D, 0x2D, 0x2D, 0x3D, 0x3D, 0x2D, 0x2D, 0x3D, 0x3D, 0x2D,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, UeRead)]
 pub struct FIoStoreTocHeader {
+    #[ue(check = "toc_magic == TOC_MAGIC", error = "Invalid TOC magic")]
     pub toc_magic: [u8; 16],
     pub version: u8,
     reserved0: u8,
@@ -107,52 +140,32 @@ pub struct FIoStoreTocHeader {
 }
 
 impl FIoStoreTocHeader {
-    pub fn from_reader(reader: &mut dyn Reader) -> io::Result<Self> {
-        let mut toc_magic = [0u8; 16];
-        reader.read_exact(&mut toc_magic)?;
-
-        const EXPECTED_MAGIC: [u8; 16] = [
-            0x2D, 0x3D, 0x3D, 0x2D, 0x2D, 0x3D, 0x3D, 0x2D, 0x2D, 0x3D, 0x3D, 0x2D, 0x2D, 0x3D,
-            0x3D, 0x2D,
-        ];
-
-        if toc_magic != EXPECTED_MAGIC {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "Invalid TOC magic",
-            ));
+    pub fn to_writer(&self, writer: &mut dyn Writer) -> io::Result<()> {
+        writer.write_all(&self.toc_magic)?;
+        writer.write_u8(self.version)?;
+        writer.write_u8(self.reserved0)?;
+        writer.write_u16(self.reserved1)?;
+        writer.write_u32(self.toc_header_size)?;
+        writer.write_u32(self.toc_entry_count)?;
+        writer.write_u32(self.toc_compressed_block_entry_count)?;
+        writer.write_u32(self.toc_compressed_block_entry_size)?;
+        writer.write_u32(self.compression_method_name_count)?;
+        writer.write_u32(self.compression_method_name_length)?;
+        writer.write_u32(self.compression_block_size)?;
+        writer.write_u32(self.directory_index_size)?;
+        writer.write_u32(self.partition_count)?;
+        writer.write_u64(self.container_id.0)?;
+        self.encryption_key_guid.to_writer(writer)?;
+        writer.write_u8(self.container_flags)?;
+        writer.write_u8(self.reserved3)?;
+        writer.write_u16(self.reserved4)?;
+        writer.write_u32(self.toc_chunk_perfect_hash_seeds_count)?;
+        writer.write_u64(self.partition_size)?;
+        writer.write_u32(self.toc_chunks_without_perfect_hash_count)?;
+        writer.write_u32(self.reserved7)?;
+        for val in &self.reserved8 {
+            writer.write_u64(*val)?;
         }
-
-        Ok(Self {
-            toc_magic,
-            version: reader.read_u8()?,
-            reserved0: reader.read_u8()?,
-            reserved1: reader.read_u16()?,
-            toc_header_size: reader.read_u32()?,
-            toc_entry_count: reader.read_u32()?,
-            toc_compressed_block_entry_count: reader.read_u32()?,
-            toc_compressed_block_entry_size: reader.read_u32()?,
-            compression_method_name_count: reader.read_u32()?,
-            compression_method_name_length: reader.read_u32()?,
-            compression_block_size: reader.read_u32()?,
-            directory_index_size: reader.read_u32()?,
-            partition_count: reader.read_u32()?,
-            container_id: FIoContainerId(reader.read_u64()?),
-            encryption_key_guid: FGuid::from_reader(reader)?,
-            container_flags: reader.read_u8()?,
-            reserved3: reader.read_u8()?,
-            reserved4: reader.read_u16()?,
-            toc_chunk_perfect_hash_seeds_count: reader.read_u32()?,
-            partition_size: reader.read_u64()?,
-            toc_chunks_without_perfect_hash_count: reader.read_u32()?,
-            reserved7: reader.read_u32()?,
-            reserved8: {
-                let mut arr = [0u64; 5];
-                for val in &mut arr {
-                    *val = reader.read_u64()?;
-                }
-                arr
-            },
-        })
+        Ok(())
     }
 }
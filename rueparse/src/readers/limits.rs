@@ -0,0 +1,21 @@
+/// Upper bounds for sizes read from untrusted headers, checked before anything is allocated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReaderLimits {
+    pub max_allocation: u64,
+    pub max_array_length: u64,
+    pub max_string_length: u64,
+}
+
+impl ReaderLimits {
+    pub const DEFAULT: ReaderLimits = ReaderLimits {
+        max_allocation: 2 << 30,
+        max_array_length: 1 << 26,
+        max_string_length: 1 << 24,
+    };
+}
+
+impl Default for ReaderLimits {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
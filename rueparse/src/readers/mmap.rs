@@ -0,0 +1,133 @@
+use memmap2::Mmap;
+use std::fs::File;
+use std::io;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+
+use super::{Endianness, Reader, ReaderLimits};
+
+/// Reader over a memory mapped file. Reads are plain memory accesses and uncompressed
+/// ranges can be borrowed with `slice` instead of being copied.
+#[derive(Debug)]
+pub struct MmapReader {
+    map: Arc<Mmap>,
+    position: u64,
+    limits: ReaderLimits,
+    endianness: Endianness,
+}
+
+impl MmapReader {
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::open_with_limits(path, ReaderLimits::DEFAULT)
+    }
+    pub fn open_with_limits<P: AsRef<Path>>(path: P, limits: ReaderLimits) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // The file must not be truncated by another process while it is mapped
+        let map = unsafe { Mmap::map(&file)? };
+        Ok(Self::with_limits(Arc::new(map), limits))
+    }
+    pub fn with_limits(map: Arc<Mmap>, limits: ReaderLimits) -> Self {
+        Self {
+            map,
+            position: 0,
+            limits,
+            endianness: Endianness::Little,
+        }
+    }
+
+    pub fn set_endianness(&mut self, endianness: Endianness) {
+        self.endianness = endianness;
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.map
+    }
+
+    fn range(&self, offset: u64, length: u64) -> io::Result<Range<usize>> {
+        match offset.checked_add(length) {
+            Some(end) if end <= self.map.len() as u64 => Ok(offset as usize..end as usize),
+            _ => Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "Range of {} bytes at {} is outside of the {} byte mapping",
+                    length,
+                    offset,
+                    self.map.len()
+                ),
+            )),
+        }
+    }
+
+    /// Borrows `length` bytes at `offset` without moving the read position.
+    pub fn slice(&self, offset: u64, length: u64) -> io::Result<&[u8]> {
+        let range = self.range(offset, length)?;
+        Ok(&self.map[range])
+    }
+
+    /// Borrows the next `count` bytes and advances past them.
+    pub fn read_slice(&mut self, count: u64) -> io::Result<&[u8]> {
+        let range = self.range(self.position, count)?;
+        self.position = range.end as u64;
+        Ok(&self.map[range])
+    }
+
+    fn read_raw<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        let mut bytes = [0u8; N];
+        bytes.copy_from_slice(self.read_slice(N as u64)?);
+        Ok(bytes)
+    }
+}
+
+impl Reader for MmapReader {
+    fn read_u8(&mut self) -> io::Result<u8> {
+        Ok(self.read_slice(1)?[0])
+    }
+
+    fn read_u16(&mut self) -> io::Result<u16> {
+        Ok(self.endianness.u16(self.read_raw()?))
+    }
+
+    fn read_u32(&mut self) -> io::Result<u32> {
+        Ok(self.endianness.u32(self.read_raw()?))
+    }
+
+    fn read_i32(&mut self) -> io::Result<i32> {
+        Ok(self.endianness.i32(self.read_raw()?))
+    }
+
+    fn read_u64(&mut self) -> io::Result<u64> {
+        Ok(self.endianness.u64(self.read_raw()?))
+    }
+
+    fn read_i64(&mut self) -> io::Result<i64> {
+        Ok(self.endianness.i64(self.read_raw()?))
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        buf.copy_from_slice(self.read_slice(buf.len() as u64)?);
+        Ok(())
+    }
+
+    // Like a file, seeking past the end is allowed and only the next read fails
+    fn seek(&mut self, pos: u64) -> io::Result<u64> {
+        self.position = pos;
+        Ok(pos)
+    }
+
+    fn position(&mut self) -> io::Result<u64> {
+        Ok(self.position)
+    }
+
+    fn len(&mut self) -> io::Result<u64> {
+        Ok(self.map.len() as u64)
+    }
+
+    fn limits(&self) -> ReaderLimits {
+        self.limits
+    }
+
+    fn endianness(&self) -> Endianness {
+        self.endianness
+    }
+}
@@ -1,8 +1,23 @@
+mod endianness;
 mod file;
+mod limits;
+#[cfg(feature = "mmap")]
+mod mmap;
+mod ueread;
 mod usmap;
+use std::collections::HashMap;
+use std::hash::Hash;
 use std::io;
 
+use crate::errors::{self, Error};
+
+pub use endianness::*;
 pub use file::*;
+pub use limits::*;
+#[cfg(feature = "mmap")]
+pub use mmap::*;
+pub use rueparse_derive::UeRead;
+pub use ueread::*;
 pub use usmap::*;
 
 pub trait Reader {
@@ -13,6 +28,220 @@ pub trait Reader {
     fn read_u64(&mut self) -> io::Result<u64>;
     fn read_i64(&mut self) -> io::Result<i64>;
     fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()>;
-    fn read_bool(&mut self) -> io::Result<bool>;
     fn seek(&mut self, pos: u64) -> io::Result<u64>;
+    fn position(&mut self) -> io::Result<u64>;
+    fn len(&mut self) -> io::Result<u64>;
+
+    fn limits(&self) -> ReaderLimits {
+        ReaderLimits::DEFAULT
+    }
+
+    fn endianness(&self) -> Endianness {
+        Endianness::Little
+    }
+
+    fn remaining(&mut self) -> io::Result<u64> {
+        Ok(self.len()?.saturating_sub(self.position()?))
+    }
+
+    /// Fails if `size` bytes of data that are not backed by the reader, like a decompressed
+    /// buffer, would exceed the allocation limit.
+    fn check_limit(&self, size: u64) -> io::Result<()> {
+        if size > self.limits().max_allocation {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Allocation of {} bytes exceeds the limit of {} bytes",
+                    size,
+                    self.limits().max_allocation
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Fails if `count` elements of `element_size` serialized bytes can not be read
+    /// from the rest of the reader or would exceed the allocation limit.
+    fn check_allocation(&mut self, count: u64, element_size: u64) -> io::Result<()> {
+        let size = match count.checked_mul(element_size) {
+            Some(s) => s,
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Allocation size overflows",
+                ));
+            }
+        };
+        self.check_limit(size)?;
+        let remaining = self.remaining()?;
+        if size > remaining {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "Length of {} bytes exceeds the remaining {} bytes",
+                    size, remaining
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Reads a `bool` stored as an `i32` that must be 0 or 1.
+    fn read_bool(&mut self) -> io::Result<bool> {
+        match self.read_i32()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            value => {
+                let offset = self.position()?.saturating_sub(4);
+                Err(Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid bool value {}", value),
+                )
+                .at(offset)
+                .into())
+            }
+        }
+    }
+
+    fn read_i8(&mut self) -> io::Result<i8> {
+        Ok(self.read_u8()? as i8)
+    }
+
+    fn read_i16(&mut self) -> io::Result<i16> {
+        Ok(self.read_u16()? as i16)
+    }
+
+    fn read_f32(&mut self) -> io::Result<f32> {
+        Ok(f32::from_bits(self.read_u32()?))
+    }
+
+    fn read_f64(&mut self) -> io::Result<f64> {
+        Ok(f64::from_bits(self.read_u64()?))
+    }
+
+    fn read_bytes(&mut self, count: usize) -> io::Result<Vec<u8>> {
+        self.check_allocation(count as u64, 1)?;
+        let mut bytes = vec![0u8; count];
+        self.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Reads an `FString`. A negative length means the string is stored as UTF-16.
+    fn read_fstring(&mut self) -> io::Result<String> {
+        let length = self.read_i32()?;
+        if length == 0 {
+            return Ok(String::new());
+        }
+        if length.unsigned_abs() as u64 > self.limits().max_string_length {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("String length {} exceeds the limit", length),
+            ));
+        }
+        let mut value = if length < 0 {
+            self.check_allocation(length.unsigned_abs() as u64, 2)?;
+            let mut chars = Vec::with_capacity(length.unsigned_abs() as usize);
+            for _ in 0..length.unsigned_abs() {
+                chars.push(self.read_u16()?);
+            }
+            match String::from_utf16(&chars) {
+                Ok(s) => s,
+                Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+            }
+        } else {
+            match String::from_utf8(self.read_bytes(length as usize)?) {
+                Ok(s) => s,
+                Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+            }
+        };
+        if value.ends_with('\0') {
+            value.pop();
+        }
+        Ok(value)
+    }
+
+    fn seek_relative(&mut self, offset: i64) -> io::Result<u64> {
+        let position = self.position()?;
+        match position.checked_add_signed(offset) {
+            Some(p) => self.seek(p),
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Seek before the start of the reader",
+            )),
+        }
+    }
+
+    fn skip(&mut self, count: u64) -> io::Result<u64> {
+        match i64::try_from(count) {
+            Ok(c) => self.seek_relative(c),
+            Err(_) => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Skip length out of range",
+            )),
+        }
+    }
+
+    /// Reads the `i32` element count that prefixes `TArray`, `TMap` and `TSet`.
+    /// Every element takes at least one byte, so the count can not exceed the remaining length.
+    fn read_array_length(&mut self) -> io::Result<usize> {
+        let length = self.read_i32()?;
+        if length < 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Negative array length",
+            ));
+        }
+        if length as u64 > self.limits().max_array_length {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Array length {} exceeds the limit", length),
+            ));
+        }
+        self.check_allocation(length as u64, 1)?;
+        Ok(length as usize)
+    }
 }
+
+/// Generic container readers. They live outside `Reader` so that it stays usable as `dyn Reader`,
+/// and are implemented for every reader including `dyn Reader` itself.
+pub trait ReaderExt: Reader {
+    /// Reads a `TArray<T>`, calling `read` for every element.
+    fn read_array<T>(
+        &mut self,
+        mut read: impl FnMut(&mut Self) -> io::Result<T>,
+    ) -> io::Result<Vec<T>> {
+        let length = self.read_array_length()?;
+        let mut values = Vec::new();
+        for i in 0..length {
+            match read(self) {
+                Ok(v) => values.push(v),
+                Err(e) => return Err(errors::at_index(e, i)),
+            }
+        }
+        Ok(values)
+    }
+
+    /// Reads a `TMap<K, V>` stored as a count followed by key value pairs.
+    fn read_map<K: Eq + Hash, V>(
+        &mut self,
+        mut read_key: impl FnMut(&mut Self) -> io::Result<K>,
+        mut read_value: impl FnMut(&mut Self) -> io::Result<V>,
+    ) -> io::Result<HashMap<K, V>> {
+        let length = self.read_array_length()?;
+        let mut values = HashMap::new();
+        for i in 0..length {
+            let key = match read_key(self) {
+                Ok(k) => k,
+                Err(e) => return Err(errors::at_index(e, i)),
+            };
+            let value = match read_value(self) {
+                Ok(v) => v,
+                Err(e) => return Err(errors::at_index(e, i)),
+            };
+            values.insert(key, value);
+        }
+        Ok(values)
+    }
+}
+
+impl<R: Reader + ?Sized> ReaderExt for R {}
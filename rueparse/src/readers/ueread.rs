@@ -0,0 +1,140 @@
+use std::io;
+
+use super::Reader;
+use crate::errors;
+
+/// Types that can be read from their serialized layout. Implemented by `#[derive(UeRead)]`.
+pub trait UeRead: Sized {
+    fn ue_read(reader: &mut dyn Reader) -> io::Result<Self>;
+}
+
+macro_rules! impl_primitive {
+    ($($ty:ty => $read:ident),* $(,)?) => {
+        $(
+            impl UeRead for $ty {
+                fn ue_read(reader: &mut dyn Reader) -> io::Result<Self> {
+                    reader.$read()
+                }
+            }
+        )*
+    };
+}
+
+impl_primitive! {
+    u8 => read_u8,
+    i8 => read_i8,
+    u16 => read_u16,
+    i16 => read_i16,
+    u32 => read_u32,
+    i32 => read_i32,
+    u64 => read_u64,
+    i64 => read_i64,
+    f32 => read_f32,
+    f64 => read_f64,
+    bool => read_bool,
+    String => read_fstring,
+}
+
+/// Fixed size arrays are stored as their elements without a length.
+impl<T: UeRead, const N: usize> UeRead for [T; N] {
+    fn ue_read(reader: &mut dyn Reader) -> io::Result<Self> {
+        let mut values = Vec::with_capacity(N);
+        for i in 0..N {
+            match T::ue_read(reader) {
+                Ok(v) => values.push(v),
+                Err(e) => return Err(errors::at_index(e, i)),
+            }
+        }
+        match values.try_into() {
+            Ok(a) => Ok(a),
+            Err(_) => unreachable!(),
+        }
+    }
+}
+
+/// `TArray<T>`, prefixed with its `i32` length.
+impl<T: UeRead> UeRead for Vec<T> {
+    fn ue_read(reader: &mut dyn Reader) -> io::Result<Self> {
+        let length = reader.read_array_length()?;
+        let mut values = Vec::new();
+        for i in 0..length {
+            match T::ue_read(reader) {
+                Ok(v) => values.push(v),
+                Err(e) => return Err(errors::at_index(e, i)),
+            }
+        }
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::readers::{FileReader, Reader, UeRead};
+    use std::io::{self, Cursor};
+
+    #[derive(Debug, PartialEq, UeRead)]
+    struct Count(u16);
+
+    fn read_doubled(reader: &mut dyn Reader) -> io::Result<u32> {
+        Ok(reader.read_u32()? * 2)
+    }
+
+    #[derive(Debug, PartialEq, UeRead)]
+    struct Sample {
+        #[ue(check = "version < 10", error = "Unsupported version")]
+        version: u8,
+        count: Count,
+        #[ue(len = "count.0")]
+        fixed: Vec<u16>,
+        #[ue(if = "version >= 2", pad = 3)]
+        gated: Option<u8>,
+        #[ue(if = "version >= 5", default = "7")]
+        old_default: i32,
+        #[ue(with = "read_doubled")]
+        doubled: u32,
+        prefixed: Vec<[u8; 2]>,
+        #[ue(skip)]
+        cached: u64,
+    }
+
+    fn data(version: u8) -> Vec<u8> {
+        let mut data = vec![version, 2, 0, 1, 0, 2, 0];
+        if version >= 2 {
+            data.extend([0xFF, 0xFF, 0xFF, 9]);
+        }
+        data.extend(21u32.to_le_bytes());
+        data.extend(1i32.to_le_bytes());
+        data.extend([3, 4]);
+        data
+    }
+
+    #[test]
+    fn derived_reads_follow_attributes() {
+        let mut reader = FileReader::new(Cursor::new(data(2)));
+        let sample = Sample::from_reader(&mut reader).unwrap();
+        assert_eq!(
+            sample,
+            Sample {
+                version: 2,
+                count: Count(2),
+                fixed: vec![1, 2],
+                gated: Some(9),
+                old_default: 7,
+                doubled: 42,
+                prefixed: vec![[3, 4]],
+                cached: 0,
+            }
+        );
+        assert_eq!(reader.remaining().unwrap(), 0);
+
+        let mut reader = FileReader::new(Cursor::new(data(1)));
+        assert_eq!(Sample::from_reader(&mut reader).unwrap().gated, None);
+
+        let mut reader = FileReader::new(Cursor::new(data(12)));
+        let error = Sample::from_reader(&mut reader).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Sample > version at offset 0x0: Unsupported version"
+        );
+    }
+}
@@ -1,27 +1,62 @@
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::ReadBytesExt;
 use std::io::{self, Cursor, Read, Seek, SeekFrom};
 
-use super::Reader;
-use crate::mappings::EUsmapVersion;
+use super::{Endianness, Reader, ReaderLimits};
+use crate::mappings::{EUsmapVersion, UsmapParserError};
 
 pub struct FUsmapReader<'a> {
     cursor: Cursor<&'a [u8]>,
     pub version: EUsmapVersion,
+    limits: ReaderLimits,
+    endianness: Endianness,
 }
 
 impl<'a> FUsmapReader<'a> {
     pub fn new(data: &'a [u8], version: EUsmapVersion) -> Self {
+        Self::with_limits(data, version, ReaderLimits::DEFAULT)
+    }
+    pub fn with_limits(data: &'a [u8], version: EUsmapVersion, limits: ReaderLimits) -> Self {
         Self {
             cursor: Cursor::new(data),
             version,
+            limits,
+            endianness: Endianness::Little,
         }
     }
-    pub fn read_name(&mut self, names: &Vec<String>) -> String {
-        let name_entry: i32 = self.read_i32().unwrap();
-        if name_entry != -1 {
-            return names.get(name_entry as usize).cloned().unwrap();
-        } else {
-            return String::new();
+    pub fn set_endianness(&mut self, endianness: Endianness) {
+        self.endianness = endianness;
+    }
+    fn read_raw<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        let mut bytes = [0u8; N];
+        self.cursor.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+    /// Runs `read`, reporting running out of data as `Truncated` at the offset it started from.
+    pub fn checked<T>(
+        &mut self,
+        read: impl FnOnce(&mut Self) -> io::Result<T>,
+    ) -> Result<T, UsmapParserError> {
+        let offset = self.cursor.position();
+        match read(self) {
+            Ok(v) => Ok(v),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                Err(UsmapParserError::Truncated { offset })
+            }
+            Err(e) => Err(UsmapParserError::ReadError(e)),
+        }
+    }
+    pub fn read_name(&mut self, names: &Vec<String>) -> Result<String, UsmapParserError> {
+        let offset = self.cursor.position();
+        let name_entry: i32 = self.checked(|r| r.read_i32())?;
+        if name_entry == -1 {
+            return Ok(String::new());
+        }
+        match usize::try_from(name_entry).ok().and_then(|i| names.get(i)) {
+            Some(name) => Ok(name.clone()),
+            None => Err(UsmapParserError::NameIndexOutOfRange {
+                index: name_entry,
+                offset,
+            }),
         }
     }
 }
@@ -32,23 +67,23 @@ impl<'a> Reader for FUsmapReader<'a> {
     }
 
     fn read_u16(&mut self) -> io::Result<u16> {
-        self.cursor.read_u16::<LittleEndian>()
+        Ok(self.endianness.u16(self.read_raw()?))
     }
 
     fn read_u32(&mut self) -> io::Result<u32> {
-        self.cursor.read_u32::<LittleEndian>()
+        Ok(self.endianness.u32(self.read_raw()?))
     }
 
     fn read_i32(&mut self) -> io::Result<i32> {
-        self.cursor.read_i32::<LittleEndian>()
+        Ok(self.endianness.i32(self.read_raw()?))
     }
 
     fn read_u64(&mut self) -> io::Result<u64> {
-        self.cursor.read_u64::<LittleEndian>()
+        Ok(self.endianness.u64(self.read_raw()?))
     }
 
     fn read_i64(&mut self) -> io::Result<i64> {
-        self.cursor.read_i64::<LittleEndian>()
+        Ok(self.endianness.i64(self.read_raw()?))
     }
 
     fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
@@ -59,19 +94,19 @@ impl<'a> Reader for FUsmapReader<'a> {
         self.cursor.seek(SeekFrom::Start(pos))
     }
 
-    fn read_bool(&mut self) -> io::Result<bool> {
-        match self.read_i32() {
-            Ok(u) => match u {
-                0 => return Ok(false),
-                1 => return Ok(true),
-                _ => {
-                    return Err(io::Error::new(
-                        io::ErrorKind::InvalidData,
-                        "Invalid TOC magic",
-                    ));
-                }
-            },
-            Err(e) => return Err(e),
-        }
+    fn position(&mut self) -> io::Result<u64> {
+        Ok(self.cursor.position())
+    }
+
+    fn len(&mut self) -> io::Result<u64> {
+        Ok(self.cursor.get_ref().len() as u64)
+    }
+
+    fn limits(&self) -> ReaderLimits {
+        self.limits
+    }
+
+    fn endianness(&self) -> Endianness {
+        self.endianness
     }
 }
@@ -0,0 +1,607 @@
+use std::borrow::Cow;
+#[cfg(not(feature = "mmap"))]
+use std::fs::File;
+use std::io::Cursor;
+#[cfg(not(feature = "mmap"))]
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+#[cfg(feature = "mmap")]
+use std::sync::OnceLock;
+
+use super::objects::{
+    EIoErrorCode, FIoDirectoryIndexResource, FIoStatus, FIoStoreTocCompressedBlockEntry,
+    FIoStoreTocResource,
+};
+use crate::compression::CompressionRegistry;
+use crate::errors;
+use crate::models::{FAesKey, FGuid};
+#[cfg(feature = "mmap")]
+use crate::readers::MmapReader;
+use crate::readers::{Endianness, FileReader, ReaderLimits};
+
+#[derive(Debug)]
+pub struct IoStoreReader {
+    pub path: String,
+    pub toc: FIoStoreTocResource,
+    pub limits: ReaderLimits,
+    pub endianness: Endianness,
+    aes_key: Option<FAesKey>,
+    // Partitions are mapped on first use
+    #[cfg(feature = "mmap")]
+    partitions: Vec<OnceLock<MmapReader>>,
+}
+
+impl IoStoreReader {
+    pub fn new(utoc_path: &str) -> Result<Self, FIoStatus> {
+        Self::with_limits(utoc_path, ReaderLimits::DEFAULT)
+    }
+
+    pub fn with_limits(utoc_path: &str, limits: ReaderLimits) -> Result<Self, FIoStatus> {
+        Self::with_options(utoc_path, limits, Endianness::Little)
+    }
+
+    /// Opens a container whose TOC and data use the given byte order, for platform specific builds.
+    pub fn with_options(
+        utoc_path: &str,
+        limits: ReaderLimits,
+        endianness: Endianness,
+    ) -> Result<Self, FIoStatus> {
+        #[cfg(feature = "mmap")]
+        let reader = MmapReader::open_with_limits(utoc_path, limits);
+        #[cfg(not(feature = "mmap"))]
+        let reader =
+            File::open(utoc_path).map(|file| FileReader::with_limits(BufReader::new(file), limits));
+        let mut reader = match reader {
+            Ok(r) => r,
+            Err(e) => return Err(FIoStatus::new(EIoErrorCode::FileOpenFailed, e.to_string())),
+        };
+        reader.set_endianness(endianness);
+        let toc = match FIoStoreTocResource::from_reader(&mut reader) {
+            Ok(t) => t,
+            Err(e) => {
+                return Err(FIoStatus::new(
+                    EIoErrorCode::CorruptToc,
+                    errors::in_file(e, utoc_path).to_string(),
+                ));
+            }
+        };
+        Ok(Self {
+            path: utoc_path.to_string(),
+            #[cfg(feature = "mmap")]
+            partitions: (0..toc.header.partition_count.max(1))
+                .map(|_| OnceLock::new())
+                .collect(),
+            toc,
+            limits,
+            endianness,
+            aes_key: None,
+        })
+    }
+
+    pub fn encryption_key_guid(&self) -> FGuid {
+        self.toc.header.encryption_key_guid
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.toc.is_encrypted()
+    }
+
+    /// Reads the directory index and keeps the key for later chunk reads.
+    pub fn mount(&mut self, key: Option<FAesKey>) -> Result<Vec<(String, u32)>, FIoStatus> {
+        let files = self.read_directory_index(key.as_ref())?;
+        self.aes_key = key;
+        Ok(files)
+    }
+
+    /// Decrypts and walks the directory index, returning every file path with its TOC entry index.
+    pub fn read_directory_index(
+        &self,
+        key: Option<&FAesKey>,
+    ) -> Result<Vec<(String, u32)>, FIoStatus> {
+        if self.toc.directory_index_buffer.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut buffer = self.toc.directory_index_buffer.clone();
+        if self.is_encrypted() {
+            let key = match key {
+                Some(k) => k,
+                None => {
+                    return Err(FIoStatus::new(
+                        EIoErrorCode::InvalidEncryptionKey,
+                        format!(
+                            "Missing key {} for {}",
+                            self.encryption_key_guid(),
+                            self.path
+                        ),
+                    ));
+                }
+            };
+            if let Err(e) = key.decrypt(&mut buffer) {
+                return Err(FIoStatus::new(
+                    EIoErrorCode::InvalidEncryptionKey,
+                    e.to_string(),
+                ));
+            }
+        }
+
+        // A wrong key produces garbage that fails to parse
+        let mut reader = FileReader::with_limits(Cursor::new(buffer), self.limits);
+        reader.set_endianness(self.endianness);
+        let index = match FIoDirectoryIndexResource::from_reader(&mut reader) {
+            Ok(i) => i,
+            Err(e) => {
+                let e = errors::in_file(e, format!("{} directory index", self.path));
+                return Err(FIoStatus::new(
+                    EIoErrorCode::InvalidEncryptionKey,
+                    format!("Failed to read directory index: {}", e),
+                ));
+            }
+        };
+        let files = match index.files() {
+            Ok(f) => f,
+            Err(e) => return Err(FIoStatus::new(EIoErrorCode::CorruptToc, e.to_string())),
+        };
+        for (path, toc_entry_index) in &files {
+            if *toc_entry_index as usize >= self.toc.chunk_offset_lengths.len() {
+                return Err(FIoStatus::new(
+                    EIoErrorCode::CorruptToc,
+                    format!("{} points to a missing TOC entry", path),
+                ));
+            }
+        }
+        Ok(files)
+    }
+
+    fn partition_path(&self, partition_index: u64) -> String {
+        let path = Path::new(&self.path);
+        let stem = path.with_extension("");
+        if partition_index == 0 {
+            format!("{}.ucas", stem.to_string_lossy())
+        } else {
+            format!("{}_s{}.ucas", stem.to_string_lossy(), partition_index)
+        }
+    }
+
+    #[cfg(feature = "mmap")]
+    fn partition(&self, partition_index: u64) -> Result<&MmapReader, FIoStatus> {
+        let cell = match self.partitions.get(partition_index as usize) {
+            Some(c) => c,
+            None => {
+                return Err(FIoStatus::new(
+                    EIoErrorCode::CorruptToc,
+                    format!("Partition {} out of range", partition_index),
+                ));
+            }
+        };
+        if let Some(reader) = cell.get() {
+            return Ok(reader);
+        }
+        let path = self.partition_path(partition_index);
+        match MmapReader::open_with_limits(&path, self.limits) {
+            // Another thread may have mapped it first, the extra mapping is dropped
+            Ok(r) => {
+                let _ = cell.set(r);
+            }
+            Err(e) => {
+                return Err(FIoStatus::new(
+                    EIoErrorCode::FileOpenFailed,
+                    format!("{}: {}", path, e),
+                ));
+            }
+        }
+        Ok(cell.get().unwrap())
+    }
+
+    /// Borrows a chunk straight from the mapped partition when it is stored uncompressed,
+    /// unencrypted and contiguous. Returns `None` when it has to go through `read_chunk`.
+    #[cfg(feature = "mmap")]
+    pub fn chunk_slice(&self, toc_entry_index: u32) -> Result<Option<&[u8]>, FIoStatus> {
+        let offset_length = match self.toc.chunk_offset_lengths.get(toc_entry_index as usize) {
+            Some(o) => o,
+            None => {
+                return Err(FIoStatus::new(
+                    EIoErrorCode::UnknownChunkID,
+                    format!("TOC entry {} not found in {}", toc_entry_index, self.path),
+                ));
+            }
+        };
+        let offset = offset_length.offset();
+        let length = offset_length.length();
+        let block_size = self.toc.header.compression_block_size as u64;
+        if length == 0 || block_size == 0 || self.is_encrypted() {
+            return Ok(None);
+        }
+        let partition_size = match self.toc.header.partition_size {
+            0 => u64::MAX,
+            size => size,
+        };
+        let first_block = offset / block_size;
+        let last_block = (offset + length - 1) / block_size;
+        let blocks = match self
+            .toc
+            .compression_blocks
+            .get(first_block as usize..=last_block as usize)
+        {
+            Some(b) => b,
+            None => {
+                return Err(FIoStatus::new(
+                    EIoErrorCode::CorruptToc,
+                    format!("Compression block {} out of range", last_block),
+                ));
+            }
+        };
+        for (i, block) in blocks.iter().enumerate() {
+            if block.compression_method_index() != 0
+                || block.compressed_size() != block.uncompressed_size()
+                || (i > 0 && block.offset() != blocks[i - 1].offset() + block_size)
+            {
+                return Ok(None);
+            }
+        }
+
+        let start = blocks[0].offset() + offset % block_size;
+        if start / partition_size != (start + length - 1) / partition_size {
+            return Ok(None);
+        }
+        let partition = self.partition(start / partition_size)?;
+        match partition.slice(start % partition_size, length) {
+            Ok(s) => Ok(Some(s)),
+            Err(e) => Err(FIoStatus::new(EIoErrorCode::ReadError, e.to_string())),
+        }
+    }
+
+    /// Reads, decrypts and decompresses the chunk behind a TOC entry.
+    /// With the `parallel` feature the compression blocks are decoded on the rayon thread pool.
+    pub fn read_chunk(
+        &self,
+        toc_entry_index: u32,
+        compression: &CompressionRegistry,
+    ) -> Result<Vec<u8>, FIoStatus> {
+        self.read_chunk_with(toc_entry_index, compression, cfg!(feature = "parallel"))
+    }
+
+    /// Reads a chunk and wraps it in a reader using the container's limits and byte order.
+    pub fn chunk_reader(
+        &self,
+        toc_entry_index: u32,
+        compression: &CompressionRegistry,
+    ) -> Result<FileReader<Cursor<Vec<u8>>>, FIoStatus> {
+        let data = self.read_chunk(toc_entry_index, compression)?;
+        let mut reader = FileReader::with_limits(Cursor::new(data), self.limits);
+        reader.set_endianness(self.endianness);
+        Ok(reader)
+    }
+
+    fn read_chunk_with(
+        &self,
+        toc_entry_index: u32,
+        compression: &CompressionRegistry,
+        parallel: bool,
+    ) -> Result<Vec<u8>, FIoStatus> {
+        let offset_length = match self.toc.chunk_offset_lengths.get(toc_entry_index as usize) {
+            Some(o) => o,
+            None => {
+                return Err(FIoStatus::new(
+                    EIoErrorCode::UnknownChunkID,
+                    format!("TOC entry {} not found in {}", toc_entry_index, self.path),
+                ));
+            }
+        };
+        let offset = offset_length.offset();
+        let length = offset_length.length();
+        if length == 0 {
+            return Ok(Vec::new());
+        }
+        if length > self.limits.max_allocation {
+            return Err(FIoStatus::new(
+                EIoErrorCode::ReadError,
+                format!("Chunk of {} bytes exceeds the allocation limit", length),
+            ));
+        }
+
+        // Validates the block range before the output is allocated
+        let blocks = self.read_raw_blocks(offset, length)?;
+        let mut output = vec![0u8; length as usize];
+
+        // Every block owns a disjoint part of the output, so they can be decoded independently
+        let mut slices = Vec::with_capacity(blocks.len());
+        let mut rest = output.as_mut_slice();
+        for block in &blocks {
+            let (head, tail) = rest.split_at_mut(block.copy_len);
+            slices.push(head);
+            rest = tail;
+        }
+
+        if parallel {
+            #[cfg(feature = "parallel")]
+            {
+                use rayon::prelude::*;
+                blocks
+                    .into_par_iter()
+                    .zip(slices)
+                    .try_for_each(|(block, out)| self.decode_block(block, out, compression))?;
+                return Ok(output);
+            }
+        }
+        for (block, out) in blocks.into_iter().zip(slices) {
+            self.decode_block(block, out, compression)?;
+        }
+        Ok(output)
+    }
+
+    /// Reads the still compressed and encrypted blocks covering `length` bytes at `offset`.
+    /// Unencrypted blocks are borrowed when the partitions are memory mapped.
+    fn read_raw_blocks(&self, offset: u64, length: u64) -> Result<Vec<RawBlock<'_>>, FIoStatus> {
+        let block_size = self.toc.header.compression_block_size as u64;
+        let partition_size = match self.toc.header.partition_size {
+            0 => u64::MAX,
+            size => size,
+        };
+        if block_size == 0 {
+            return Err(FIoStatus::new(
+                EIoErrorCode::CorruptToc,
+                String::from("Compression block size is zero"),
+            ));
+        }
+        let first_block = offset / block_size;
+        let last_block = (offset + length - 1) / block_size;
+        if last_block >= self.toc.compression_blocks.len() as u64 {
+            return Err(FIoStatus::new(
+                EIoErrorCode::CorruptToc,
+                format!("Compression block {} out of range", last_block),
+            ));
+        }
+
+        let mut blocks = Vec::with_capacity((last_block - first_block + 1) as usize);
+        #[cfg(not(feature = "mmap"))]
+        let mut partition: Option<(u64, File)> = None;
+        for block_index in first_block..=last_block {
+            let entry = match self.toc.compression_blocks.get(block_index as usize) {
+                Some(b) => *b,
+                None => {
+                    return Err(FIoStatus::new(
+                        EIoErrorCode::CorruptToc,
+                        format!("Compression block {} out of range", block_index),
+                    ));
+                }
+            };
+            let compressed_size = entry.compressed_size() as usize;
+            let raw_size = if self.is_encrypted() {
+                (compressed_size + 15) & !15
+            } else {
+                compressed_size
+            };
+
+            let partition_index = entry.offset() / partition_size;
+            #[cfg(feature = "mmap")]
+            let raw = {
+                let partition = self.partition(partition_index)?;
+                Cow::Borrowed(partition.slice(entry.offset() % partition_size, raw_size as u64)?)
+            };
+            #[cfg(not(feature = "mmap"))]
+            let raw = {
+                if partition.as_ref().map(|(i, _)| *i) != Some(partition_index) {
+                    let path = self.partition_path(partition_index);
+                    match File::open(&path) {
+                        Ok(f) => partition = Some((partition_index, f)),
+                        Err(e) => {
+                            return Err(FIoStatus::new(
+                                EIoErrorCode::FileOpenFailed,
+                                format!("{}: {}", path, e),
+                            ));
+                        }
+                    }
+                }
+                let file = &mut partition.as_mut().unwrap().1;
+                let mut raw = vec![0u8; raw_size];
+                file.seek(SeekFrom::Start(entry.offset() % partition_size))?;
+                file.read_exact(&mut raw)?;
+                Cow::Owned(raw)
+            };
+
+            // The part of the block that overlaps the chunk
+            let block_start = block_index * block_size;
+            let copy_start = offset.max(block_start);
+            let copy_end = (offset + length).min(block_start + entry.uncompressed_size() as u64);
+            if copy_end <= copy_start {
+                return Err(FIoStatus::new(
+                    EIoErrorCode::CorruptToc,
+                    format!("Compression block {} is too small", block_index),
+                ));
+            }
+            blocks.push(RawBlock {
+                entry,
+                raw,
+                copy_offset: (copy_start - block_start) as usize,
+                copy_len: (copy_end - copy_start) as usize,
+            });
+        }
+        Ok(blocks)
+    }
+
+    fn decode_block(
+        &self,
+        mut block: RawBlock<'_>,
+        output: &mut [u8],
+        compression: &CompressionRegistry,
+    ) -> Result<(), FIoStatus> {
+        if self.is_encrypted() {
+            let key = match &self.aes_key {
+                Some(k) => k,
+                None => {
+                    return Err(FIoStatus::new(
+                        EIoErrorCode::InvalidEncryptionKey,
+                        format!("{} is not unlocked", self.path),
+                    ));
+                }
+            };
+            // Borrowed blocks are copied before they are decrypted in place
+            if let Err(e) = key.decrypt(block.raw.to_mut()) {
+                return Err(FIoStatus::new(
+                    EIoErrorCode::InvalidEncryptionKey,
+                    e.to_string(),
+                ));
+            }
+        }
+
+        let method_index = block.entry.compression_method_index();
+        let method_name = match self.toc.compression_methods.get(method_index as usize) {
+            Some(m) => m,
+            None => {
+                return Err(FIoStatus::new(
+                    EIoErrorCode::CorruptToc,
+                    format!("Compression method index {} out of range", method_index),
+                ));
+            }
+        };
+        let input = &block.raw[..block.entry.compressed_size() as usize];
+        let uncompressed_size = block.entry.uncompressed_size() as usize;
+
+        // Whole blocks are decoded straight into the output, partial ones through a scratch buffer
+        // Unknown method names only fail here, so containers using them still mount
+        let result = if block.copy_offset == 0 && block.copy_len == uncompressed_size {
+            compression.decompress(method_name, input, output)
+        } else {
+            let mut uncompressed = vec![0u8; uncompressed_size];
+            compression
+                .decompress(method_name, input, &mut uncompressed)
+                .map(|_| {
+                    output.copy_from_slice(
+                        &uncompressed[block.copy_offset..block.copy_offset + block.copy_len],
+                    )
+                })
+        };
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => Err(FIoStatus::new(
+                EIoErrorCode::ReadError,
+                format!("Failed to decompress {} block: {:?}", method_name, e),
+            )),
+        }
+    }
+}
+
+struct RawBlock<'a> {
+    entry: FIoStoreTocCompressedBlockEntry,
+    raw: Cow<'a, [u8]>,
+    copy_offset: usize,
+    copy_len: usize,
+}
+
+#[cfg(all(test, any(feature = "parallel", feature = "mmap")))]
+mod tests {
+    use super::IoStoreReader;
+    use crate::compression::CompressionRegistry;
+    use flate2::{Compression, write::ZlibEncoder};
+    use std::io::Write;
+    #[cfg(feature = "parallel")]
+    use std::time::Instant;
+
+    const BLOCK_SIZE: usize = 0x10000;
+
+    // Writes an unencrypted, unindexed container holding one chunk, zlib compressed or stored
+    fn write_container(dir: &std::path::Path, data: &[u8], compress: bool) -> String {
+        let mut ucas = Vec::new();
+        let mut blocks = Vec::new();
+        for block in data.chunks(BLOCK_SIZE) {
+            let compressed = if compress {
+                let mut encoder = ZlibEncoder::new(Vec::new(), Compression::fast());
+                encoder.write_all(block).unwrap();
+                encoder.finish().unwrap()
+            } else {
+                block.to_vec()
+            };
+            let mut entry = (ucas.len() as u64).to_le_bytes()[..5].to_vec();
+            entry.extend(&(compressed.len() as u32).to_le_bytes()[..3]);
+            entry.extend(&(block.len() as u32).to_le_bytes()[..3]);
+            entry.push(compress as u8);
+            blocks.push(entry);
+            ucas.extend(compressed);
+        }
+
+        let mut toc = [0x2D, 0x3D, 0x3D, 0x2D].repeat(4);
+        toc.extend([8, 0, 0, 0]);
+        for value in [
+            144,
+            1,
+            blocks.len() as u32,
+            12,
+            1,
+            32,
+            BLOCK_SIZE as u32,
+            0,
+            1,
+        ] {
+            toc.extend(value.to_le_bytes());
+        }
+        toc.extend([0u8; 24]);
+        toc.extend([0u8; 8]);
+        toc.extend(u64::MAX.to_le_bytes());
+        toc.extend([0u8; 48]);
+        toc.extend([0u8; 12]);
+        toc.extend(&0u64.to_be_bytes()[3..]);
+        toc.extend(&(data.len() as u64).to_be_bytes()[3..]);
+        for entry in blocks {
+            toc.extend(entry);
+        }
+        let mut name = [0u8; 32];
+        name[..4].copy_from_slice(b"Zlib");
+        toc.extend(name);
+
+        std::fs::write(dir.join("container.ucas"), ucas).unwrap();
+        let path = dir.join("container.utoc");
+        std::fs::write(&path, toc).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    fn sample(len: usize) -> Vec<u8> {
+        (0..len as u32)
+            .map(|i| (i ^ (i >> 7)).wrapping_mul(2654435761).to_le_bytes()[3] & 0x3F)
+            .collect()
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn borrows_stored_chunks_from_mapping() {
+        let dir = std::env::temp_dir().join(format!("rueparse_mmap_chunks_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let compression = CompressionRegistry::default();
+        let data = sample(BLOCK_SIZE * 3 + 100);
+
+        let stored = IoStoreReader::new(&write_container(&dir, &data, false)).unwrap();
+        assert_eq!(stored.chunk_slice(0).unwrap(), Some(data.as_slice()));
+        assert_eq!(stored.read_chunk(0, &compression).unwrap(), data);
+
+        let compressed = IoStoreReader::new(&write_container(&dir, &data, true)).unwrap();
+        assert_eq!(compressed.chunk_slice(0).unwrap(), None);
+        assert_eq!(compressed.read_chunk(0, &compression).unwrap(), data);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // cargo test --release --features parallel -- --ignored --nocapture
+    #[cfg(feature = "parallel")]
+    #[test]
+    #[ignore]
+    fn compare_parallel_with_sequential() {
+        let dir =
+            std::env::temp_dir().join(format!("rueparse_parallel_bench_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let data = sample(256 * 1024 * 1024);
+        let reader = IoStoreReader::new(&write_container(&dir, &data, true)).unwrap();
+        let compression = CompressionRegistry::default();
+
+        let start = Instant::now();
+        let sequential = reader.read_chunk_with(0, &compression, false).unwrap();
+        let sequential_time = start.elapsed();
+        let start = Instant::now();
+        let parallel = reader.read_chunk_with(0, &compression, true).unwrap();
+        let parallel_time = start.elapsed();
+
+        println!(
+            "sequential: {:?}, parallel: {:?}",
+            sequential_time, parallel_time
+        );
+        assert!(sequential == data && parallel == data);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
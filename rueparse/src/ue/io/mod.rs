@@ -0,0 +1,4 @@
+mod iostorereader;
+pub mod objects;
+
+pub use iostorereader::*;
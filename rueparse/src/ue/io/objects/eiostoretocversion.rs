@@ -0,0 +1,13 @@
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EIoStoreTocVersion {
+    Invalid = 0,
+    Initial,
+    DirectoryIndex,
+    PartitionSize,
+    PerfectHash,
+    PerfectHashWithOverflow,
+    OnDemandMetaData,
+    RemovedOnDemandMetaData,
+    ReplaceIoChunkHashWithIoHash,
+}
@@ -0,0 +1,20 @@
+use crate::readers::UeRead;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, UeRead)]
+pub struct FIoChunkId {
+    pub chunk_id: u64,
+    pub chunk_index: u16,
+    padding: u8,
+    pub chunk_type: u8,
+}
+
+impl FIoChunkId {
+    pub fn new(chunk_id: u64, chunk_index: u16, chunk_type: u8) -> Self {
+        Self {
+            chunk_id,
+            chunk_index,
+            padding: 0,
+            chunk_type,
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use std::io;
+
+use crate::readers::UeRead;
+
+const INVALID_HANDLE: u32 = u32::MAX;
+
+#[derive(Debug, Clone, Copy, UeRead)]
+pub struct FIoDirectoryIndexEntry {
+    pub name: u32,
+    pub first_child_entry: u32,
+    pub next_sibling_entry: u32,
+    pub first_file_entry: u32,
+}
+
+#[derive(Debug, Clone, Copy, UeRead)]
+pub struct FIoFileIndexEntry {
+    pub name: u32,
+    pub next_file_entry: u32,
+    pub user_data: u32,
+}
+
+#[derive(Debug, UeRead)]
+pub struct FIoDirectoryIndexResource {
+    pub mount_point: String,
+    pub directory_entries: Vec<FIoDirectoryIndexEntry>,
+    pub file_entries: Vec<FIoFileIndexEntry>,
+    pub string_table: Vec<String>,
+}
+
+impl FIoDirectoryIndexResource {
+    /// Returns every file in the index as its full path and TOC entry index.
+    pub fn files(&self) -> io::Result<Vec<(String, u32)>> {
+        let mount_point = self
+            .mount_point
+            .strip_prefix("../../../")
+            .unwrap_or(&self.mount_point);
+        let mut files = Vec::with_capacity(self.file_entries.len());
+        if self.directory_entries.is_empty() {
+            return Ok(files);
+        }
+
+        // Directories still to visit with their parent's path. Children are pushed last so a
+        // directory's subtree is listed before its next sibling.
+        let mut pending = vec![(0, mount_point.to_string())];
+        let mut visited = 0;
+        while let Some((directory_index, path)) = pending.pop() {
+            // A well-formed index visits every entry at most once
+            visited += 1;
+            if visited > self.directory_entries.len() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Directory index contains a cycle",
+                ));
+            }
+            let directory = match self.directory_entries.get(directory_index as usize) {
+                Some(d) => d,
+                None => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "Directory entry out of range",
+                    ));
+                }
+            };
+            let directory_path = if directory.name == INVALID_HANDLE {
+                path.clone()
+            } else {
+                format!("{}{}/", path, self.name(directory.name)?)
+            };
+
+            let mut file_index = directory.first_file_entry;
+            while file_index != INVALID_HANDLE {
+                let file = match self.file_entries.get(file_index as usize) {
+                    Some(f) => f,
+                    None => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            "File entry out of range",
+                        ));
+                    }
+                };
+                files.push((
+                    format!("{}{}", directory_path, self.name(file.name)?),
+                    file.user_data,
+                ));
+                if files.len() > self.file_entries.len() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "Directory index contains a cycle",
+                    ));
+                }
+                file_index = file.next_file_entry;
+            }
+
+            if directory.next_sibling_entry != INVALID_HANDLE {
+                pending.push((directory.next_sibling_entry, path));
+            }
+            if directory.first_child_entry != INVALID_HANDLE {
+                pending.push((directory.first_child_entry, directory_path));
+            }
+        }
+        Ok(files)
+    }
+
+    fn name(&self, index: u32) -> io::Result<&str> {
+        match self.string_table.get(index as usize) {
+            Some(s) => Ok(s),
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Directory index name out of range",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        FIoDirectoryIndexEntry, FIoDirectoryIndexResource, FIoFileIndexEntry, INVALID_HANDLE,
+    };
+
+    fn directory(
+        name: u32,
+        first_child_entry: u32,
+        next_sibling_entry: u32,
+        first_file_entry: u32,
+    ) -> FIoDirectoryIndexEntry {
+        FIoDirectoryIndexEntry {
+            name,
+            first_child_entry,
+            next_sibling_entry,
+            first_file_entry,
+        }
+    }
+
+    fn file(name: u32, user_data: u32) -> FIoFileIndexEntry {
+        FIoFileIndexEntry {
+            name,
+            next_file_entry: INVALID_HANDLE,
+            user_data,
+        }
+    }
+
+    #[test]
+    fn lists_subdirectories_before_siblings() {
+        let index = FIoDirectoryIndexResource {
+            mount_point: "../../../Game/".to_string(),
+            directory_entries: vec![
+                directory(INVALID_HANDLE, 1, INVALID_HANDLE, INVALID_HANDLE),
+                directory(0, 2, 3, 0),
+                directory(1, INVALID_HANDLE, INVALID_HANDLE, 1),
+                directory(2, INVALID_HANDLE, INVALID_HANDLE, 2),
+            ],
+            file_entries: vec![file(3, 7), file(4, 8), file(5, 9)],
+            string_table: ["Maps", "Sub", "Ui", "a.umap", "b.uasset", "c.uasset"]
+                .map(String::from)
+                .to_vec(),
+        };
+        let files = index.files().unwrap();
+        assert_eq!(
+            files,
+            [
+                ("Game/Maps/a.umap".to_string(), 7),
+                ("Game/Maps/Sub/b.uasset".to_string(), 8),
+                ("Game/Ui/c.uasset".to_string(), 9),
+            ]
+        );
+    }
+
+    #[test]
+    fn walks_deep_directory_chains() {
+        // Every directory is the only child of the one before
+        let depth = 200_000;
+        let mut directory_entries: Vec<_> = (1..depth)
+            .map(|i| directory(INVALID_HANDLE, i, INVALID_HANDLE, INVALID_HANDLE))
+            .collect();
+        directory_entries.push(directory(INVALID_HANDLE, INVALID_HANDLE, INVALID_HANDLE, 0));
+        let mut index = FIoDirectoryIndexResource {
+            mount_point: "Game/".to_string(),
+            directory_entries,
+            file_entries: vec![file(0, 1)],
+            string_table: vec!["deep.uasset".to_string()],
+        };
+        assert_eq!(
+            index.files().unwrap(),
+            [("Game/deep.uasset".to_string(), 1)]
+        );
+
+        // Pointing the last one back at the first makes it endless
+        index.directory_entries[depth as usize - 1].first_child_entry = 0;
+        assert!(index.files().is_err());
+    }
+}
@@ -0,0 +1,20 @@
+use crate::readers::UeRead;
+
+/// Two 40-bit big endian values packed into 10 bytes.
+#[derive(Debug, Clone, Copy, UeRead)]
+pub struct FIoOffsetAndLength {
+    offset_and_length: [u8; 10],
+}
+
+impl FIoOffsetAndLength {
+    pub fn offset(&self) -> u64 {
+        self.offset_and_length[..5]
+            .iter()
+            .fold(0u64, |acc, b| (acc << 8) | *b as u64)
+    }
+    pub fn length(&self) -> u64 {
+        self.offset_and_length[5..]
+            .iter()
+            .fold(0u64, |acc, b| (acc << 8) | *b as u64)
+    }
+}
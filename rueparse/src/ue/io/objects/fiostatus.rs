@@ -1,4 +1,6 @@
-#[derive(Debug)]
+use std::io;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EIoErrorCode {
     Ok,
     Unknown,
@@ -33,3 +35,9 @@ impl FIoStatus {
         format!("{} ({:?})", self.error_message, self.error_code)
     }
 }
+
+impl From<io::Error> for FIoStatus {
+    fn from(e: io::Error) -> Self {
+        FIoStatus::new(EIoErrorCode::ReadError, e.to_string())
+    }
+}
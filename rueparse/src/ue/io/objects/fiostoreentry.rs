@@ -0,0 +1,18 @@
+use crate::fileprovider::objects::GameFile;
+
+#[derive(Debug)]
+pub struct FIoStoreEntry {
+    pub game_file: GameFile,
+    pub container_index: usize,
+    pub toc_entry_index: u32,
+}
+
+impl FIoStoreEntry {
+    pub fn new(path: String, size: i64, container_index: usize, toc_entry_index: u32) -> Self {
+        Self {
+            game_file: GameFile::new(path, size),
+            container_index,
+            toc_entry_index,
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use crate::readers::UeRead;
+
+/// 40-bit offset, 24-bit compressed size, 24-bit uncompressed size and an 8-bit
+/// compression method index, all little endian.
+#[derive(Debug, Clone, Copy, UeRead)]
+pub struct FIoStoreTocCompressedBlockEntry {
+    data: [u8; 12],
+}
+
+impl FIoStoreTocCompressedBlockEntry {
+    pub fn offset(&self) -> u64 {
+        self.data[..5]
+            .iter()
+            .rev()
+            .fold(0u64, |acc, b| (acc << 8) | *b as u64)
+    }
+    pub fn compressed_size(&self) -> u32 {
+        self.data[5..8]
+            .iter()
+            .rev()
+            .fold(0u32, |acc, b| (acc << 8) | *b as u32)
+    }
+    pub fn uncompressed_size(&self) -> u32 {
+        self.data[8..11]
+            .iter()
+            .rev()
+            .fold(0u32, |acc, b| (acc << 8) | *b as u32)
+    }
+    pub fn compression_method_index(&self) -> u8 {
+        self.data[11]
+    }
+}
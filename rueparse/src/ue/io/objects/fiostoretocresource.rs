@@ -0,0 +1,107 @@
+use std::io;
+
+use super::{EIoStoreTocVersion, FIoChunkId, FIoOffsetAndLength, FIoStoreTocCompressedBlockEntry};
+use crate::readers::{EIoContainerFlags, FIoStoreTocHeader, Reader};
+
+#[derive(Debug)]
+pub struct FIoStoreTocResource {
+    pub header: FIoStoreTocHeader,
+    pub chunk_ids: Vec<FIoChunkId>,
+    pub chunk_offset_lengths: Vec<FIoOffsetAndLength>,
+    pub chunk_perfect_hash_seeds: Vec<i32>,
+    pub chunk_indices_without_perfect_hash: Vec<i32>,
+    pub compression_blocks: Vec<FIoStoreTocCompressedBlockEntry>,
+    pub compression_methods: Vec<String>,
+    pub directory_index_buffer: Vec<u8>,
+}
+
+impl FIoStoreTocResource {
+    pub fn from_reader(reader: &mut dyn Reader) -> io::Result<Self> {
+        let header = FIoStoreTocHeader::from_reader(reader)?;
+        reader.seek(header.toc_header_size as u64)?;
+
+        // Chunk ids and offset lengths take 12 and 10 bytes per entry
+        reader.check_allocation(header.toc_entry_count as u64, 22)?;
+        let mut chunk_ids = Vec::with_capacity(header.toc_entry_count as usize);
+        for _ in 0..header.toc_entry_count {
+            chunk_ids.push(FIoChunkId::from_reader(reader)?);
+        }
+        let mut chunk_offset_lengths = Vec::with_capacity(header.toc_entry_count as usize);
+        for _ in 0..header.toc_entry_count {
+            chunk_offset_lengths.push(FIoOffsetAndLength::from_reader(reader)?);
+        }
+
+        let mut chunk_perfect_hash_seeds = Vec::new();
+        if header.version >= EIoStoreTocVersion::PerfectHash as u8 {
+            reader.check_allocation(header.toc_chunk_perfect_hash_seeds_count as u64, 4)?;
+            for _ in 0..header.toc_chunk_perfect_hash_seeds_count {
+                chunk_perfect_hash_seeds.push(reader.read_i32()?);
+            }
+        }
+        let mut chunk_indices_without_perfect_hash = Vec::new();
+        if header.version >= EIoStoreTocVersion::PerfectHashWithOverflow as u8 {
+            reader.check_allocation(header.toc_chunks_without_perfect_hash_count as u64, 4)?;
+            for _ in 0..header.toc_chunks_without_perfect_hash_count {
+                chunk_indices_without_perfect_hash.push(reader.read_i32()?);
+            }
+        }
+
+        reader.check_allocation(header.toc_compressed_block_entry_count as u64, 12)?;
+        let mut compression_blocks =
+            Vec::with_capacity(header.toc_compressed_block_entry_count as usize);
+        for _ in 0..header.toc_compressed_block_entry_count {
+            compression_blocks.push(FIoStoreTocCompressedBlockEntry::from_reader(reader)?);
+        }
+
+        // Index 0 is always "no compression"
+        let mut compression_methods = vec![String::from("None")];
+        reader.check_allocation(
+            header.compression_method_name_count as u64,
+            header.compression_method_name_length as u64,
+        )?;
+        let mut name_buffer = vec![0u8; header.compression_method_name_length as usize];
+        for _ in 0..header.compression_method_name_count {
+            reader.read_exact(&mut name_buffer)?;
+            let end = name_buffer
+                .iter()
+                .position(|b| *b == 0)
+                .unwrap_or(name_buffer.len());
+            compression_methods.push(String::from_utf8_lossy(&name_buffer[..end]).into_owned());
+        }
+
+        if header.container_flags & EIoContainerFlags::Signed as u8 != 0 {
+            let hash_size = reader.read_i32()?;
+            if hash_size < 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Invalid TOC signature size",
+                ));
+            }
+            // TOC signature, block signature and one SHA1 hash per compressed block
+            reader.skip(hash_size as u64 * 2 + 20 * compression_blocks.len() as u64)?;
+        }
+
+        let mut directory_index_buffer = Vec::new();
+        if header.version >= EIoStoreTocVersion::DirectoryIndex as u8
+            && header.container_flags & EIoContainerFlags::Indexed as u8 != 0
+            && header.directory_index_size > 0
+        {
+            directory_index_buffer = reader.read_bytes(header.directory_index_size as usize)?;
+        }
+
+        Ok(Self {
+            header,
+            chunk_ids,
+            chunk_offset_lengths,
+            chunk_perfect_hash_seeds,
+            chunk_indices_without_perfect_hash,
+            compression_blocks,
+            compression_methods,
+            directory_index_buffer,
+        })
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.header.container_flags & EIoContainerFlags::Encrypted as u8 != 0
+    }
+}
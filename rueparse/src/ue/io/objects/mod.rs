@@ -1,7 +1,21 @@
+mod eiostoretocversion;
+mod fiochunkid;
 mod fiocontainerid;
+mod fiodirectoryindexresource;
+mod fiooffsetandlength;
 mod fiostatus;
+mod fiostoreentry;
+mod fiostoretoccompressedblockentry;
+mod fiostoretocresource;
 mod fpackageid;
 
+pub use eiostoretocversion::*;
+pub use fiochunkid::*;
 pub use fiocontainerid::*;
+pub use fiodirectoryindexresource::*;
+pub use fiooffsetandlength::*;
 pub use fiostatus::*;
+pub use fiostoreentry::*;
+pub use fiostoretoccompressedblockentry::*;
+pub use fiostoretocresource::*;
 pub use fpackageid::*;
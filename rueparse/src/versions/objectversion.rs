@@ -1,3 +1,8 @@
+use std::io;
+
+use crate::readers::UeRead;
+use crate::writers::Writer;
+
 #[repr(u32)]
 #[derive(Debug)]
 #[allow(non_camel_case_types)]
@@ -738,7 +743,7 @@ impl EUnrealEngineObjectLicenseeUEVersion {
         EUnrealEngineObjectLicenseeUEVersion::VER_LIC_NONE;
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, UeRead)]
 pub struct FPackageFileVersion {
     pub file_version_ue4: i32,
     pub file_version_ue5: i32,
@@ -750,6 +755,10 @@ impl FPackageFileVersion {
             file_version_ue5,
         }
     }
+    pub fn to_writer(&self, writer: &mut dyn Writer) -> io::Result<()> {
+        writer.write_i32(self.file_version_ue4)?;
+        writer.write_i32(self.file_version_ue5)
+    }
     pub fn create_ue4_version(version: i32) -> Self {
         Self {
             file_version_ue4: version,
@@ -0,0 +1,74 @@
+use crate::readers::Endianness;
+use crate::writers::Writer;
+use std::io::{self, Write};
+
+/// Writer over any `Write`. The position is counted, so the inner writer does not need to seek.
+pub struct FileWriter<W: Write> {
+    inner: W,
+    position: u64,
+    endianness: Endianness,
+}
+impl<W: Write> FileWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            position: 0,
+            endianness: Endianness::Little,
+        }
+    }
+    pub fn set_endianness(&mut self, endianness: Endianness) {
+        self.endianness = endianness;
+    }
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+    fn write_raw<const N: usize>(&mut self, little: [u8; N], big: [u8; N]) -> io::Result<()> {
+        match self.endianness {
+            Endianness::Little => self.write_all(&little),
+            Endianness::Big => self.write_all(&big),
+        }
+    }
+}
+impl<W: Write> Writer for FileWriter<W> {
+    fn write_u8(&mut self, value: u8) -> io::Result<()> {
+        self.write_all(&[value])
+    }
+
+    fn write_u16(&mut self, value: u16) -> io::Result<()> {
+        self.write_raw(value.to_le_bytes(), value.to_be_bytes())
+    }
+
+    fn write_u32(&mut self, value: u32) -> io::Result<()> {
+        self.write_raw(value.to_le_bytes(), value.to_be_bytes())
+    }
+
+    fn write_i32(&mut self, value: i32) -> io::Result<()> {
+        self.write_raw(value.to_le_bytes(), value.to_be_bytes())
+    }
+
+    fn write_u64(&mut self, value: u64) -> io::Result<()> {
+        self.write_raw(value.to_le_bytes(), value.to_be_bytes())
+    }
+
+    fn write_i64(&mut self, value: i64) -> io::Result<()> {
+        self.write_raw(value.to_le_bytes(), value.to_be_bytes())
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.inner.write_all(buf)?;
+        self.position += buf.len() as u64;
+        Ok(())
+    }
+
+    fn write_bool(&mut self, value: bool) -> io::Result<()> {
+        self.write_i32(value as i32)
+    }
+
+    fn position(&mut self) -> io::Result<u64> {
+        Ok(self.position)
+    }
+
+    fn endianness(&self) -> Endianness {
+        self.endianness
+    }
+}
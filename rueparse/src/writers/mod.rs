@@ -0,0 +1,176 @@
+mod file;
+use std::io;
+
+pub use file::*;
+
+use crate::readers::Endianness;
+
+/// Counterpart of `Reader`, with the same primitives and layouts.
+pub trait Writer {
+    fn write_u8(&mut self, value: u8) -> io::Result<()>;
+    fn write_u16(&mut self, value: u16) -> io::Result<()>;
+    fn write_u32(&mut self, value: u32) -> io::Result<()>;
+    fn write_i32(&mut self, value: i32) -> io::Result<()>;
+    fn write_u64(&mut self, value: u64) -> io::Result<()>;
+    fn write_i64(&mut self, value: i64) -> io::Result<()>;
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()>;
+    fn write_bool(&mut self, value: bool) -> io::Result<()>;
+    fn position(&mut self) -> io::Result<u64>;
+
+    fn endianness(&self) -> Endianness {
+        Endianness::Little
+    }
+
+    fn write_i8(&mut self, value: i8) -> io::Result<()> {
+        self.write_u8(value as u8)
+    }
+
+    fn write_i16(&mut self, value: i16) -> io::Result<()> {
+        self.write_u16(value as u16)
+    }
+
+    fn write_f32(&mut self, value: f32) -> io::Result<()> {
+        self.write_u32(value.to_bits())
+    }
+
+    fn write_f64(&mut self, value: f64) -> io::Result<()> {
+        self.write_u64(value.to_bits())
+    }
+
+    /// Writes an `FString`. ASCII strings are stored as bytes, anything else as UTF-16
+    /// with a negative length, like the engine does.
+    fn write_fstring(&mut self, value: &str) -> io::Result<()> {
+        if value.is_empty() {
+            return self.write_i32(0);
+        }
+        if value.is_ascii() {
+            self.write_i32(string_length(value.len() + 1)?)?;
+            self.write_all(value.as_bytes())?;
+            return self.write_u8(0);
+        }
+        let chars: Vec<u16> = value.encode_utf16().collect();
+        self.write_i32(-string_length(chars.len() + 1)?)?;
+        for c in chars {
+            self.write_u16(c)?;
+        }
+        self.write_u16(0)
+    }
+
+    /// Writes the `i32` element count that prefixes `TArray`, `TMap` and `TSet`.
+    fn write_array_length(&mut self, length: usize) -> io::Result<()> {
+        match i32::try_from(length) {
+            Ok(l) => self.write_i32(l),
+            Err(_) => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Array length does not fit in an i32",
+            )),
+        }
+    }
+}
+
+fn string_length(length: usize) -> io::Result<i32> {
+    match i32::try_from(length) {
+        Ok(l) => Ok(l),
+        Err(_) => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "String length does not fit in an i32",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FileWriter, Writer};
+    use crate::models::FGuid;
+    use crate::objects::core::serialization::{FCustomVersion, FCustomVersionContainer};
+    use crate::readers::{Endianness, FIoStoreTocHeader, FileReader, Reader};
+    use crate::versions::FPackageFileVersion;
+    use std::io::{self, Cursor};
+
+    fn round_trip<T: PartialEq + std::fmt::Debug>(
+        value: &T,
+        endianness: Endianness,
+        write: impl Fn(&T, &mut dyn Writer) -> io::Result<()>,
+        read: impl Fn(&mut dyn Reader) -> io::Result<T>,
+    ) -> Vec<u8> {
+        let mut writer = FileWriter::new(Vec::new());
+        writer.set_endianness(endianness);
+        write(value, &mut writer).unwrap();
+        let data = writer.into_inner();
+
+        let mut reader = FileReader::new(Cursor::new(data.clone()));
+        reader.set_endianness(endianness);
+        assert_eq!(&read(&mut reader).unwrap(), value);
+        assert_eq!(reader.remaining().unwrap(), 0);
+        data
+    }
+
+    #[test]
+    fn guid_and_versions_round_trip() {
+        let guid = FGuid::new(0x01020304, 0xDEADBEEF, 0, u32::MAX);
+        for endianness in [Endianness::Little, Endianness::Big] {
+            round_trip(&guid, endianness, FGuid::to_writer, FGuid::from_reader);
+
+            let version = FCustomVersion::new(guid, 42);
+            round_trip(
+                &version,
+                endianness,
+                FCustomVersion::to_writer,
+                FCustomVersion::from_reader,
+            );
+
+            let container = FCustomVersionContainer::from_versions(vec![
+                version.clone(),
+                FCustomVersion::new(FGuid::new(5, 6, 7, 8), -1),
+            ]);
+            round_trip(
+                &container,
+                endianness,
+                FCustomVersionContainer::to_writer,
+                |r| FCustomVersionContainer::new(r, None),
+            );
+
+            round_trip(
+                &FPackageFileVersion::new(522, 1012),
+                endianness,
+                FPackageFileVersion::to_writer,
+                FPackageFileVersion::from_reader,
+            );
+        }
+    }
+
+    #[test]
+    fn toc_header_round_trips_byte_for_byte() {
+        let mut data = [0x2D, 0x3D, 0x3D, 0x2D].repeat(4);
+        data.extend([8, 1, 2, 3]);
+        data.extend((1..=124u32).map(|i| (i * 7) as u8));
+        let mut reader = FileReader::new(Cursor::new(data.clone()));
+        let header = FIoStoreTocHeader::from_reader(&mut reader).unwrap();
+
+        let written = round_trip(
+            &header,
+            Endianness::Little,
+            FIoStoreTocHeader::to_writer,
+            FIoStoreTocHeader::from_reader,
+        );
+        assert_eq!(written, data);
+        round_trip(
+            &header,
+            Endianness::Big,
+            FIoStoreTocHeader::to_writer,
+            FIoStoreTocHeader::from_reader,
+        );
+    }
+
+    #[test]
+    fn fstrings_round_trip() {
+        for value in ["", "Engine", "Ünïcode ✓"] {
+            round_trip(
+                &value.to_string(),
+                Endianness::Little,
+                |v, w| w.write_fstring(v),
+                |r| r.read_fstring(),
+            );
+        }
+    }
+}